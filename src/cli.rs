@@ -1,6 +1,8 @@
 use clap::{Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
 
+use crate::config::{SurrealdbEngine, SurrealdbStorage};
+
 #[derive(Parser, Debug)]
 #[clap(name = "ultime", version, author = "Odonno")]
 /// The ultimate full-stack experience
@@ -10,6 +12,27 @@ pub struct Args {
     /// Open browser when app is launched
     #[clap(short, long)]
     pub open: bool,
+    /// Storage of the SurrealDB instance: `memory`, `file:<path>` or `rocksdb:<path>` (file storages require the `surreal` cli)
+    #[clap(long)]
+    pub storage: Option<SurrealdbStorage>,
+    /// Engine used to start SurrealDB: the `surreal` cli or an experimental embedded server (defaults to `auto`)
+    #[clap(long)]
+    pub db_engine: Option<SurrealdbEngine>,
+    /// Wipe the SurrealDB storage before starting, then re-apply migrations
+    #[clap(long)]
+    pub reset: bool,
+    /// Port of the leptos app (defaults to the port of `app.address`, or the next available one)
+    #[clap(long)]
+    pub port: Option<u16>,
+    /// Port of the SurrealDB instance (defaults to the port of `surrealdb.address`, or the next available one)
+    #[clap(long)]
+    pub db_port: Option<u16>,
+    /// Hide the logs of the SurrealDB instance (they are still written to the log file)
+    #[clap(long)]
+    pub quiet_db: bool,
+    /// Print the logs instead of displaying the interactive dashboard
+    #[clap(long)]
+    pub no_tui: bool,
 }
 
 #[derive(ValueEnum, Debug, Clone)]
@@ -34,6 +57,39 @@ pub enum Action {
         #[command(subcommand)]
         command: GenerateAction,
     },
+    /// Apply, revert or create SurrealDB migrations on the configured database
+    Migrate {
+        #[command(subcommand)]
+        command: MigrateAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MigrateAction {
+    /// Apply schemas, events and pending migrations
+    Up {
+        /// Apply the migrations up to this one (included)
+        #[clap(long)]
+        to: Option<String>,
+    },
+    /// Revert the last applied migration
+    Down {
+        /// Revert every migration applied after this one
+        #[clap(long)]
+        to: Option<String>,
+    },
+    /// Display applied and pending migrations
+    Status,
+    /// List applied migrations with their execution date
+    List,
+    /// Create a new migration file inside the `/migrations` folder
+    New {
+        /// Name of the migration, e.g. `AddPost`
+        name: String,
+        /// Also create the down migration inside the `/migrations/down` folder
+        #[clap(long)]
+        down: bool,
+    },
 }
 
 #[derive(ValueEnum, Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +103,15 @@ pub enum GenerateEndpointFromSchemaMethod {
     DeleteAll,
 }
 
+/// How the generated endpoint signs in to SurrealDB.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GenerateEndpointAuth {
+    /// Shared connection with the root user of `ultime.toml`, for admin endpoints
+    Root,
+    /// Connection authenticated with the `access_token` cookie, enforcing the table permissions
+    Scope,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum GenerateAction {
     /// Generate `db` module inside the `/db` folder
@@ -54,6 +119,9 @@ pub enum GenerateAction {
         /// Watch file changes to re-generate the `db` module
         #[clap(short, long)]
         watch: bool,
+        /// Check that the `db` module is up to date, without writing anything
+        #[clap(long, conflicts_with = "watch")]
+        check: bool,
     },
     /// Generate a new leptos component inside the `/components` folder
     #[clap(aliases = vec!["c"])]
@@ -67,6 +135,18 @@ pub enum GenerateAction {
         /// Name of the page to generate
         name: String,
     },
+    /// Generate a new migration file inside the `/migrations` folder
+    #[clap(aliases = vec!["m"])]
+    Migration {
+        /// Name of the migration, e.g. `AddTagToPost`
+        name: String,
+        /// Also generate the down migration inside the `/migrations/down` folder
+        #[clap(long)]
+        down: bool,
+        /// Pre-fill the migration with the statements applying the changes of `/schemas` since the last migration of the `/migrations` folder, and the down migration with the statements reverting them
+        #[clap(long)]
+        diff_from_last_migration: bool,
+    },
     /// Generate a new leptos endpoint inside the `/api` folder
     Endpoint {
         /// Name of the api endpoint to generate
@@ -86,5 +166,17 @@ pub enum GenerateAction {
         /// Method to use for the generated endpoint from schema
         #[clap(long)]
         method: Option<GenerateEndpointFromSchemaMethod>,
+        /// Authentication used by the endpoint to access the database
+        #[clap(long, value_enum, default_value_t = GenerateEndpointAuth::Root)]
+        auth: GenerateEndpointAuth,
+    },
+    /// Generate the CRUD endpoints and the pages of a table, inside the `/api` and `/pages` folders
+    #[clap(aliases = vec!["r"])]
+    Resource {
+        /// Name of the table defined in `/schemas`
+        table: String,
+        /// Authentication used by the endpoints to access the database
+        #[clap(long, value_enum, default_value_t = GenerateEndpointAuth::Root)]
+        auth: GenerateEndpointAuth,
     },
 }
@@ -0,0 +1,377 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display},
+    net::{IpAddr, Ipv4Addr},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+pub const CONFIG_FILE_NAME: &str = "ultime.toml";
+
+/// Address of the SurrealDB instance started by `ultime`, read by the generated endpoints at runtime.
+pub const SURREALDB_ADDRESS_ENV: &str = "ULTIME_SURREALDB_ADDRESS";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct UltimeConfig {
+    pub surrealdb: SurrealdbConfig,
+    pub app: AppConfig,
+    pub folders: FoldersConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SurrealdbConfig {
+    pub address: String,
+    pub username: String,
+    pub password: String,
+    pub ns: String,
+    pub db: String,
+    pub storage: SurrealdbStorage,
+    pub engine: SurrealdbEngine,
+}
+
+/// How the dev SurrealDB instance is started.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SurrealdbEngine {
+    /// Use the `surreal` cli if installed, the embedded server otherwise
+    #[default]
+    Auto,
+    /// Run `surreal start`
+    Cli,
+    /// Run an experimental in-process SurrealDB server, the data of the `file:` and `rocksdb:` storages being saved to a SurrealQL export
+    Embedded,
+}
+
+/// Where the data of the dev SurrealDB instance is stored, e.g. `memory` or `file:target/ultime/surrealdb`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SurrealdbStorage {
+    #[default]
+    Memory,
+    File(PathBuf),
+    RocksDb(PathBuf),
+}
+
+impl SurrealdbStorage {
+    /// Path argument of the `surreal start` command.
+    pub fn to_surreal_path(&self) -> String {
+        match self {
+            SurrealdbStorage::Memory => "memory".to_string(),
+            SurrealdbStorage::File(path) => format!("file://{}", path.display()),
+            SurrealdbStorage::RocksDb(path) => format!("rocksdb://{}", path.display()),
+        }
+    }
+
+    /// Folder containing the data, `None` when the data is not persisted.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            SurrealdbStorage::Memory => None,
+            SurrealdbStorage::File(path) | SurrealdbStorage::RocksDb(path) => Some(path),
+        }
+    }
+}
+
+impl FromStr for SurrealdbStorage {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let (kind, path) = match value.split_once(':') {
+            Some((kind, path)) => (kind, Some(path.trim_start_matches("//"))),
+            None => (value, None),
+        };
+
+        match (kind, path) {
+            ("memory", None) => Ok(SurrealdbStorage::Memory),
+            ("file", Some(path)) if !path.is_empty() => Ok(SurrealdbStorage::File(path.into())),
+            ("rocksdb", Some(path)) if !path.is_empty() => {
+                Ok(SurrealdbStorage::RocksDb(path.into()))
+            }
+            _ => Err(anyhow!(
+                "Invalid storage '{}', expected 'memory', 'file:<path>' or 'rocksdb:<path>'",
+                value
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for SurrealdbStorage {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<SurrealdbStorage> for String {
+    fn from(storage: SurrealdbStorage) -> Self {
+        storage.to_string()
+    }
+}
+
+impl Display for SurrealdbStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SurrealdbStorage::Memory => write!(f, "memory"),
+            SurrealdbStorage::File(path) => write!(f, "file:{}", path.display()),
+            SurrealdbStorage::RocksDb(path) => write!(f, "rocksdb:{}", path.display()),
+        }
+    }
+}
+
+impl Default for SurrealdbConfig {
+    fn default() -> Self {
+        Self {
+            address: "localhost:8000".to_string(),
+            username: "root".to_string(),
+            password: "root".to_string(),
+            ns: "test".to_string(),
+            db: "test".to_string(),
+            storage: SurrealdbStorage::Memory,
+            engine: SurrealdbEngine::Auto,
+        }
+    }
+}
+
+impl SurrealdbConfig {
+    pub fn port(&self) -> Option<u16> {
+        extract_port(&self.address)
+    }
+
+    /// Interface the instance started by `ultime` listens on, only the local one unless an IP is configured.
+    pub fn bind_host(&self) -> IpAddr {
+        extract_bind_host(&self.address)
+    }
+
+    pub fn set_port(&mut self, port: u16) {
+        self.address = replace_port(&self.address, port);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub address: String,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            address: "localhost:3000".to_string(),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Interface the leptos app listens on, only the local one unless an IP is configured.
+    pub fn bind_host(&self) -> IpAddr {
+        extract_bind_host(&self.address)
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        extract_port(&self.address)
+    }
+
+    pub fn set_port(&mut self, port: u16) {
+        self.address = replace_port(&self.address, port);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FoldersConfig {
+    pub schemas: String,
+    pub events: String,
+    pub queries: String,
+    pub mutations: String,
+}
+
+impl Default for FoldersConfig {
+    fn default() -> Self {
+        Self {
+            schemas: "schemas".to_string(),
+            events: "events".to_string(),
+            queries: "queries".to_string(),
+            mutations: "mutations".to_string(),
+        }
+    }
+}
+
+/// Read the `ultime.toml` file of the current project, if any.
+/// Every missing value falls back to the default configuration.
+pub fn load_config() -> Result<UltimeConfig> {
+    let config_file = Path::new(CONFIG_FILE_NAME);
+
+    if !config_file.exists() {
+        return Ok(UltimeConfig::default());
+    }
+
+    let config_content = std::fs::read_to_string(config_file)?;
+    parse_config(&config_content)
+}
+
+fn parse_config(content: &str) -> Result<UltimeConfig> {
+    let config = toml::from_str::<UltimeConfig>(content).context(format!(
+        "Cannot parse configuration file '{}'",
+        CONFIG_FILE_NAME
+    ))?;
+
+    Ok(config)
+}
+
+fn extract_port(address: &str) -> Option<u16> {
+    address.rsplit_once(':')?.1.parse::<u16>().ok()
+}
+
+/// IP of the host of an address, `127.0.0.1` when the host is a name (e.g. `localhost`).
+fn extract_bind_host(address: &str) -> IpAddr {
+    let host = match address.rsplit_once(':') {
+        Some((host, _)) => host,
+        None => address,
+    };
+
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+fn replace_port(address: &str, port: u16) -> String {
+    match address.rsplit_once(':') {
+        Some((host, _)) => format!("{}:{}", host, port),
+        None => format!("{}:{}", address, port),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_empty_config() {
+        let config = parse_config("").unwrap();
+
+        assert_eq!(config, UltimeConfig::default());
+    }
+
+    #[test]
+    fn parse_partial_config() {
+        let config = parse_config(
+            r#"[surrealdb]
+address = "localhost:8001"
+ns = "blog"
+
+[folders]
+queries = "db/queries"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.surrealdb.address, "localhost:8001");
+        assert_eq!(config.surrealdb.username, "root");
+        assert_eq!(config.surrealdb.ns, "blog");
+        assert_eq!(config.surrealdb.db, "test");
+        assert_eq!(config.surrealdb.port(), Some(8001));
+        assert_eq!(config.app.url(), "http://localhost:3000");
+        assert_eq!(config.folders.queries, "db/queries");
+        assert_eq!(config.folders.schemas, "schemas");
+    }
+
+    #[test]
+    fn parse_template_config() {
+        const CONFIG_CONTENT: &str = include_str!("../templates/projects/blog/ultime.toml");
+
+        let config = parse_config(CONFIG_CONTENT).unwrap();
+
+        assert_eq!(config, UltimeConfig::default());
+    }
+
+    #[test]
+    fn parse_storage_config() {
+        let config = parse_config(
+            r#"[surrealdb]
+storage = "file:target/ultime/surrealdb"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.surrealdb.storage,
+            SurrealdbStorage::File(PathBuf::from("target/ultime/surrealdb"))
+        );
+        assert_eq!(
+            config.surrealdb.storage.to_surreal_path(),
+            "file://target/ultime/surrealdb"
+        );
+    }
+
+    #[test]
+    fn parse_storage_values() {
+        assert_eq!(
+            "memory".parse::<SurrealdbStorage>().unwrap(),
+            SurrealdbStorage::Memory
+        );
+        assert_eq!(
+            "rocksdb:data".parse::<SurrealdbStorage>().unwrap(),
+            SurrealdbStorage::RocksDb(PathBuf::from("data"))
+        );
+        assert_eq!(
+            "file://data".parse::<SurrealdbStorage>().unwrap(),
+            SurrealdbStorage::File(PathBuf::from("data"))
+        );
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_storage() {
+        for value in ["disk", "file:", "memory:data"] {
+            let result = value.parse::<SurrealdbStorage>();
+
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                format!(
+                    "Invalid storage '{}', expected 'memory', 'file:<path>' or 'rocksdb:<path>'",
+                    value
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn replace_port_of_addresses() {
+        let mut config = UltimeConfig::default();
+
+        config.surrealdb.set_port(8001);
+        config.app.set_port(3002);
+
+        assert_eq!(config.surrealdb.address, "localhost:8001");
+        assert_eq!(config.app.url(), "http://localhost:3002");
+        assert_eq!(replace_port("localhost", 3000), "localhost:3000");
+    }
+
+    #[test]
+    fn extract_bind_host_of_addresses() {
+        assert_eq!(extract_bind_host("localhost:8000").to_string(), "127.0.0.1");
+        assert_eq!(extract_bind_host("0.0.0.0:8000").to_string(), "0.0.0.0");
+        assert_eq!(
+            extract_bind_host("192.168.1.10:8000").to_string(),
+            "192.168.1.10"
+        );
+        assert_eq!(extract_bind_host("[::1]:8000").to_string(), "::1");
+        assert_eq!(extract_bind_host("my-host").to_string(), "127.0.0.1");
+    }
+
+    #[test]
+    fn fails_to_parse_invalid_config() {
+        let result = parse_config("[surrealdb]\naddress = 8000");
+
+        assert!(result.is_err());
+    }
+}
@@ -2,4 +2,6 @@ mod common;
 pub mod component;
 pub mod db;
 pub mod endpoint;
+pub mod migration;
 pub mod page;
+pub mod resource;
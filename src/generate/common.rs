@@ -1,6 +1,7 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QueryVariable {
@@ -8,6 +9,165 @@ pub struct QueryVariable {
     pub type_: String,
 }
 
+/// How a generated module is declared in its mod file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModuleDeclaration {
+    /// `pub mod x;`
+    Public,
+    /// `mod x;`
+    Private,
+    /// `mod x;` and `pub use self::x::*;`, like the server functions of `src/api.rs`
+    ReExported,
+}
+
+pub fn ensures_folder_exists(dir_path: &Path) -> Result<()> {
+    if !dir_path.exists() {
+        fs_extra::dir::create_all(dir_path, false)?;
+    }
+
+    Ok(())
+}
+
+pub fn ensures_file_does_not_exist(file_path: &Path) -> Result<()> {
+    match file_path.exists() {
+        true => Err(anyhow!("File {} already exists", file_path.display())),
+        false => Ok(()),
+    }
+}
+
+/// Mod file of a folder of modules, `src/pages/mod.rs` if it exists or else `src/pages.rs`.
+pub fn get_mod_file_path(dir_path: &Path) -> PathBuf {
+    let mod_file_path = dir_path.join("mod.rs");
+
+    match mod_file_path.exists() {
+        true => mod_file_path,
+        false => dir_path.with_extension("rs"),
+    }
+}
+
+/// Declare a new module in a mod file (e.g. `src/pages.rs`), the file is created if needed.
+/// Declarations are inserted in sorted order and the rest of the file is left untouched.
+pub fn register_module(
+    mod_file_path: &Path,
+    module_name: &str,
+    declaration: ModuleDeclaration,
+) -> Result<()> {
+    let content = match mod_file_path.exists() {
+        true => std::fs::read_to_string(mod_file_path)?,
+        false => String::new(),
+    };
+
+    let new_content = add_module_declaration(&content, module_name, declaration)?;
+    if new_content != content {
+        std::fs::write(mod_file_path, new_content)?;
+    }
+
+    Ok(())
+}
+
+/// Declare a generated module in the mod file of its folder (e.g. `src/components.rs`),
+/// and the folder itself in `src/lib.rs` so that the new file is compiled right away.
+pub fn register_generated_module(
+    dir_path: &Path,
+    module_name: &str,
+    declaration: ModuleDeclaration,
+    folder_declaration: ModuleDeclaration,
+) -> Result<()> {
+    register_module(&get_mod_file_path(dir_path), module_name, declaration)?;
+
+    let folder_name = dir_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context(format!("Invalid folder {}", dir_path.display()))?;
+    let lib_file_path = dir_path
+        .parent()
+        .map(|src_dir| src_dir.join("lib.rs"))
+        .filter(|path| path.exists());
+
+    if let Some(lib_file_path) = lib_file_path {
+        register_module(&lib_file_path, folder_name, folder_declaration)?;
+    }
+
+    Ok(())
+}
+
+fn add_module_declaration(
+    content: &str,
+    module_name: &str,
+    declaration: ModuleDeclaration,
+) -> Result<String> {
+    let mod_regex = Regex::new(r#"^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)\s*;"#)?;
+    let use_regex = Regex::new(r#"^\s*pub\s+use\s+self::(\w+)::\*\s*;"#)?;
+
+    let mut lines = content
+        .lines()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+
+    let mod_line = match declaration {
+        ModuleDeclaration::Public => format!("pub mod {};", module_name),
+        ModuleDeclaration::Private | ModuleDeclaration::ReExported => {
+            format!("mod {};", module_name)
+        }
+    };
+    insert_sorted_line(&mut lines, &mod_regex, module_name, mod_line);
+
+    if declaration == ModuleDeclaration::ReExported {
+        let use_line = format!("pub use self::{}::*;", module_name);
+        insert_sorted_line(&mut lines, &use_regex, module_name, use_line);
+    }
+
+    Ok(match lines.is_empty() {
+        true => String::new(),
+        false => format!("{}\n", lines.join("\n")),
+    })
+}
+
+/// Insert `line` among the lines matching `regex`, sorted by the module name they capture.
+/// Nothing is inserted if a line already refers to the module.
+fn insert_sorted_line(lines: &mut Vec<String>, regex: &Regex, module_name: &str, line: String) {
+    let declared_modules = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            regex
+                .captures(line)
+                .map(|captures| (index, captures[1].to_string()))
+        })
+        .collect::<Vec<_>>();
+
+    if declared_modules.iter().any(|(_, name)| name == module_name) {
+        return;
+    }
+
+    let next_module = declared_modules
+        .iter()
+        .find(|(_, name)| name.as_str() > module_name);
+
+    match (next_module, declared_modules.last()) {
+        (Some((index, _)), _) => {
+            // 💡 keep the attributes and doc comments of the next module above it
+            let mut index = *index;
+            while index > 0 && is_attribute_or_doc_comment(&lines[index - 1]) {
+                index -= 1;
+            }
+            lines.insert(index, line);
+        }
+        (None, Some((index, _))) => lines.insert(index + 1, line),
+        (None, None) => {
+            if lines.last().is_some_and(|last| !last.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(line);
+        }
+    }
+}
+
+fn is_attribute_or_doc_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("#[") || line.starts_with("///")
+}
+
 pub fn extract_query_variables(input: &str) -> Result<Vec<QueryVariable>> {
     let variable_regex = Regex::new(r#"^\s*(?:/{2,}|#+)\s*\$(\w+)\s*[:]\s*(\S+)\s*$"#)?;
 
@@ -30,6 +190,91 @@ pub fn extract_query_variables(input: &str) -> Result<Vec<QueryVariable>> {
     Ok(variables)
 }
 
+/// Which statement results are returned by a query/mutation with multiple statements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueryResultMode {
+    /// Only the result of the last statement that returns data
+    #[default]
+    Last,
+    /// A tuple with the result of every statement that returns data
+    All,
+}
+
+/// The result mode can be set with a comment like `// @result: all`, `# @result: all` or `-- @result: all`.
+pub fn extract_query_result_mode(input: &str) -> Result<QueryResultMode> {
+    let result_mode_regex = Regex::new(r#"^\s*(?:/{2,}|#+|-{2,})\s*@result\s*[:]\s*(\S+)\s*$"#)?;
+
+    let result_mode = input
+        .lines()
+        .find_map(|line| result_mode_regex.captures(line))
+        .map(|capture| capture[1].to_string());
+
+    match result_mode.as_deref() {
+        None | Some("last") => Ok(QueryResultMode::Last),
+        Some("all") => Ok(QueryResultMode::All),
+        Some(value) => Err(anyhow!(
+            "Invalid result mode '{}', expected 'last' or 'all'",
+            value
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OptionalField {
+    pub table: String,
+    pub field: String,
+}
+
+/// The SurrealQL parser does not know about `option<T>` kinds yet.
+/// Replace them by their inner kind and keep track of the fields defined as optional.
+pub fn strip_option_kinds(input: &str) -> Result<(String, Vec<OptionalField>)> {
+    let option_kind_regex = Regex::new(
+        r#"(?i)DEFINE\s+FIELD\s+(\S+)\s+ON\s+(?:TABLE\s+)?(\w+)\s+TYPE\s+(option\s*<)"#,
+    )?;
+
+    let mut output = String::new();
+    let mut optional_fields = vec![];
+    let mut last_index = 0;
+
+    for capture in option_kind_regex.captures_iter(input) {
+        let option_kind = capture.get(3).context("Cannot get option kind")?;
+
+        // 💡 the inner kind can contain other kinds, e.g. `option<array<record(user)>>`
+        let Some(inner_kind_end) = find_closing_angle_bracket(input, option_kind.end()) else {
+            continue;
+        };
+
+        output.push_str(&input[last_index..option_kind.start()]);
+        output.push_str(input[option_kind.end()..inner_kind_end].trim());
+        last_index = inner_kind_end + 1;
+
+        optional_fields.push(OptionalField {
+            table: capture[2].to_string(),
+            field: capture[1].to_string(),
+        });
+    }
+
+    output.push_str(&input[last_index..]);
+
+    Ok((output, optional_fields))
+}
+
+/// Index of the `>` closing the kind that starts at `start`, skipping the nested kinds.
+fn find_closing_angle_bracket(input: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+
+    for (index, character) in input[start..].char_indices() {
+        match character {
+            '<' => depth += 1,
+            '>' if depth == 0 => return Some(start + index),
+            '>' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,4 +331,207 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn should_use_last_result_mode_by_default() {
+        const QUERY_CONTENT: &str =
+            include_str!("../../templates/projects/blog/mutations/comment.surql");
+
+        let result_mode = extract_query_result_mode(QUERY_CONTENT).unwrap();
+
+        assert_eq!(result_mode, QueryResultMode::Last);
+    }
+
+    #[test]
+    fn should_extract_result_mode() {
+        let result_mode = extract_query_result_mode(
+            "// $post_id: String
+// @result: all
+
+SELECT * FROM post;",
+        )
+        .unwrap();
+
+        assert_eq!(result_mode, QueryResultMode::All);
+    }
+
+    #[test]
+    fn should_extract_result_mode_from_dash_comment() {
+        let result_mode = extract_query_result_mode(
+            "-- @result: all
+
+SELECT * FROM post;",
+        )
+        .unwrap();
+
+        assert_eq!(result_mode, QueryResultMode::All);
+    }
+
+    #[test]
+    fn fails_to_extract_invalid_result_mode() {
+        let result = extract_query_result_mode("# @result: first");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_not_strip_anything_from_schema_without_option_kind() {
+        const SCHEMA_CONTENT: &str =
+            include_str!("../../templates/projects/blog/schemas/user.surql");
+
+        let (output, optional_fields) = strip_option_kinds(SCHEMA_CONTENT).unwrap();
+
+        assert_eq!(output, SCHEMA_CONTENT);
+        assert!(optional_fields.is_empty());
+    }
+
+    #[test]
+    fn should_strip_option_kinds_from_schema() {
+        const SCHEMA_CONTENT: &str = "DEFINE FIELD name ON user TYPE string;
+DEFINE FIELD avatar ON TABLE user TYPE option<string>;
+DEFINE FIELD author ON post TYPE option< record (user) > ASSERT $value != NONE;";
+
+        let (output, optional_fields) = strip_option_kinds(SCHEMA_CONTENT).unwrap();
+
+        assert_eq!(
+            output,
+            "DEFINE FIELD name ON user TYPE string;
+DEFINE FIELD avatar ON TABLE user TYPE string;
+DEFINE FIELD author ON post TYPE record (user) ASSERT $value != NONE;"
+        );
+        assert_eq!(
+            optional_fields,
+            vec![
+                OptionalField {
+                    table: "user".to_string(),
+                    field: "avatar".to_string()
+                },
+                OptionalField {
+                    table: "post".to_string(),
+                    field: "author".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_insert_module_declaration_in_sorted_order() {
+        const CONTENT: &str = "// Pages of the app
+pub mod home;
+#[cfg(feature = \"ssr\")]
+pub mod post;
+pub mod sign_in;
+";
+
+        let output = add_module_declaration(CONTENT, "my_page", ModuleDeclaration::Public).unwrap();
+
+        assert_eq!(
+            output,
+            "// Pages of the app
+pub mod home;
+pub mod my_page;
+#[cfg(feature = \"ssr\")]
+pub mod post;
+pub mod sign_in;
+"
+        );
+    }
+
+    #[test]
+    fn should_insert_module_declaration_and_re_export() {
+        const CONTENT: &str = "mod posts;
+mod sign_in;
+
+pub use self::posts::*;
+pub use self::sign_in::*;
+";
+
+        let output =
+            add_module_declaration(CONTENT, "publish_post", ModuleDeclaration::ReExported).unwrap();
+
+        assert_eq!(
+            output,
+            "mod posts;
+mod publish_post;
+mod sign_in;
+
+pub use self::posts::*;
+pub use self::publish_post::*;
+pub use self::sign_in::*;
+"
+        );
+    }
+
+    #[test]
+    fn should_not_insert_module_declaration_twice() {
+        const CONTENT: &str = "pub mod app;
+mod pages;
+use cfg_if::cfg_if;
+";
+
+        let output = add_module_declaration(CONTENT, "pages", ModuleDeclaration::Private).unwrap();
+
+        assert_eq!(output, CONTENT);
+    }
+
+    #[test]
+    fn should_declare_module_in_empty_mod_file() {
+        let output = add_module_declaration("", "posts", ModuleDeclaration::ReExported).unwrap();
+
+        assert_eq!(output, "mod posts;\n\npub use self::posts::*;\n");
+    }
+
+    #[test]
+    fn should_strip_nested_option_kinds_from_schema() {
+        const SCHEMA_CONTENT: &str = "DEFINE FIELD tags ON post TYPE option<array<record(tag)>>;
+DEFINE FIELD scores ON post TYPE option< array<int> > ASSERT $value != NONE;";
+
+        let (output, optional_fields) = strip_option_kinds(SCHEMA_CONTENT).unwrap();
+
+        assert_eq!(
+            output,
+            "DEFINE FIELD tags ON post TYPE array<record(tag)>;
+DEFINE FIELD scores ON post TYPE array<int> ASSERT $value != NONE;"
+        );
+        assert_eq!(
+            optional_fields,
+            vec![
+                OptionalField {
+                    table: "post".to_string(),
+                    field: "tags".to_string()
+                },
+                OptionalField {
+                    table: "post".to_string(),
+                    field: "scores".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_strip_option_kinds_from_multiline_definitions() {
+        const SCHEMA_CONTENT: &str = "DEFINE FIELD avatar
+    ON TABLE user
+    TYPE option<
+        string
+    >
+    ASSERT $value = NONE OR string::len($value) > 0;";
+
+        let (output, optional_fields) = strip_option_kinds(SCHEMA_CONTENT).unwrap();
+
+        assert_eq!(
+            output,
+            "DEFINE FIELD avatar
+    ON TABLE user
+    TYPE string
+    ASSERT $value = NONE OR string::len($value) > 0;"
+        );
+        assert_eq!(
+            optional_fields,
+            vec![OptionalField {
+                table: "user".to_string(),
+                field: "avatar".to_string()
+            }]
+        );
+    }
 }
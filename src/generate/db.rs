@@ -1,64 +1,413 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local};
 use convert_case::{Case, Casing};
 use include_dir::{include_dir, Dir};
 use itertools::Itertools;
 use minijinja::{context, Environment};
 use notify::{
-    event::{AccessKind, AccessMode},
+    event::{AccessKind, AccessMode, ModifyKind},
     EventKind, RecommendedWatcher, RecursiveMode, Watcher,
 };
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 use surrealdb::sql::{
     statements::{
         DefineEventStatement, DefineFieldStatement, DefineStatement, DefineTableStatement,
     },
-    Function, Kind, Statement, Value,
+    Function, Kind, Operator, Statement, Value,
+};
+
+use crate::{
+    config::{self, FoldersConfig, SurrealdbConfig, UltimeConfig, SURREALDB_ADDRESS_ENV},
+    logger::{self, LogSource},
 };
 
-use super::common::{extract_query_variables, QueryVariable};
+use super::common::{
+    ensures_folder_exists, extract_query_result_mode, extract_query_variables, strip_option_kinds,
+    OptionalField, QueryResultMode, QueryVariable,
+};
+
+use self::{
+    diagnostics::{parse_surql_file, ParseErrors},
+    manifest::DbChanges,
+    models::{infer_response_structs, TableDefinitions},
+};
+
+mod diagnostics;
+mod manifest;
+mod models;
 
 enum SurrealType {
     Id,
+    Any,
+    Bool,
+    Bytes,
+    Datetime,
+    Decimal,
+    Duration,
+    Float,
+    Int,
+    Number,
+    Object,
     String,
+    Geometry,
+    Struct(String),
     Array(Box<SurrealType>),
     Unknown,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
-struct StructField {
+pub struct StructField {
+    pub name: String,
+    pub type_str: String,
+    pub is_optional: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct NestedStruct {
     name: String,
-    type_str: String,
+    fields: Vec<StructField>,
 }
 
-pub fn main(watch: bool) -> Result<()> {
-    generate_db_folder()?;
+/// Content of the files to write, indexed by their path.
+#[derive(Debug, Default)]
+struct DbFiles {
+    /// Files owned by ultime, tracked in the manifest
+    generated: BTreeMap<PathBuf, String>,
+    /// Files owned by the user, only updated when a model stub is added
+    models: BTreeMap<PathBuf, String>,
+    /// Source files that cannot be parsed, skipped by the generation
+    errors: ParseErrors,
+}
 
-    if watch {
-        // 💡 prevent watcher to be dropped
-        let _watcher = watch_to_regenerate_db_folder()?;
+/// Modules of the `db` folder, in the order of their declaration in `db.rs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum DbModule {
+    Crud,
+    Events,
+    Mutations,
+    Queries,
+}
 
-        // 💡 infinite loop to keep the process alive
-        loop {
-            std::thread::sleep(Duration::from_secs(1));
+impl DbModule {
+    const ALL: [DbModule; 4] = [
+        DbModule::Crud,
+        DbModule::Events,
+        DbModule::Mutations,
+        DbModule::Queries,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            DbModule::Crud => "crud",
+            DbModule::Events => "events",
+            DbModule::Mutations => "mutations",
+            DbModule::Queries => "queries",
         }
     }
+}
+
+/// Generated files of each module, kept in memory so that a single module can be regenerated.
+#[derive(Debug, Default)]
+struct DbModules(BTreeMap<DbModule, BTreeMap<PathBuf, String>>);
+
+impl DbModules {
+    /// The `connection` module is generated along with the other modules, only on the server side.
+    fn to_generated_files(
+        &self,
+        surrealdb_config: &SurrealdbConfig,
+    ) -> Result<BTreeMap<PathBuf, String>> {
+        let mut generated_files = BTreeMap::new();
+        let mut mod_file_modules = vec![];
+
+        for (module, files) in &self.0 {
+            if files.is_empty() {
+                continue;
+            }
+
+            mod_file_modules.push(module.name());
+            generated_files.extend(files.clone());
+        }
+
+        if !mod_file_modules.is_empty() {
+            let connection_file_path = Path::new("src").join("db").join("connection.rs");
+            let connection_content = generate_from_connection_template(surrealdb_config)?;
+            generated_files.insert(connection_file_path, connection_content);
+
+            let mod_file_path = Path::new("src").join("db.rs");
+            let mod_file_content = generate_from_db_mod_template(&mod_file_modules)?;
+            generated_files.insert(mod_file_path, mod_file_content);
+        }
+
+        Ok(generated_files)
+    }
+}
+
+/// Result of a statement returned by a query/mutation.
+/// `structs` is `None` when the type cannot be inferred and should be defined in the `models` folder.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct QueryResult {
+    index: usize,
+    response_type: String,
+    structs: Option<Vec<NestedStruct>>,
+}
+
+const WATCH_DEBOUNCE_DURATION: Duration = Duration::from_millis(200);
+
+/// Result of the last generation of the `db` module.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenerationStatus {
+    Generated {
+        time: DateTime<Local>,
+        changes: usize,
+    },
+    UpToDate {
+        time: DateTime<Local>,
+    },
+    Failed {
+        time: DateTime<Local>,
+        message: String,
+    },
+}
+
+impl GenerationStatus {
+    fn new(result: &Result<DbChanges>) -> Self {
+        let time = Local::now();
+
+        match result {
+            Ok(changes) if changes.is_empty() => Self::UpToDate { time },
+            Ok(changes) => Self::Generated {
+                time,
+                changes: changes.added.len() + changes.changed.len() + changes.removed.len(),
+            },
+            Err(error) => Self::Failed {
+                time,
+                message: format!("{:#}", error),
+            },
+        }
+    }
+}
+
+/// Status of the last generation, shared with the dashboard of `ultime run`.
+pub type SharedGenerationStatus = Arc<Mutex<Option<GenerationStatus>>>;
+
+pub fn main(watch: bool, check: bool) -> Result<()> {
+    let config = config::load_config()?;
+
+    if check {
+        return check_db_folder(&config);
+    }
+
+    let status = SharedGenerationStatus::default();
+
+    if !watch {
+        return generate_db_folder(&config, &status);
+    }
+
+    // 💡 prevent watcher to be dropped
+    let _watcher = watch_to_regenerate_db_folder(&config, &status)?;
+
+    // 💡 infinite loop to keep the process alive
+    loop {
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+pub fn generate_db_folder(config: &UltimeConfig, status: &SharedGenerationStatus) -> Result<()> {
+    regenerate_db_modules(config, &DbModule::ALL, &mut DbModules::default(), status)
+}
+
+/// Run the generation in memory and print the diff with the files on disk.
+/// Fails if the `db` module is not up to date.
+fn check_db_folder(config: &UltimeConfig) -> Result<()> {
+    let db_files = generate_db_files(config)?;
+
+    if !db_files.errors.is_empty() {
+        return Err(db_files.errors.report());
+    }
+
+    let mut expected_files = db_files
+        .generated
+        .iter()
+        .chain(db_files.models.iter())
+        .map(|(file_path, content)| (file_path.to_path_buf(), Some(content.to_string())))
+        .collect::<BTreeMap<_, _>>();
+
+    for orphan_file_path in manifest::find_orphan_files(&db_files.generated)? {
+        expected_files.insert(orphan_file_path, None);
+    }
+
+    let mut has_drift = false;
+
+    for (file_path, expected_content) in &expected_files {
+        let current_content = match file_path.exists() {
+            true => Some(std::fs::read_to_string(file_path)?),
+            false => None,
+        };
+
+        if &current_content == expected_content {
+            continue;
+        }
+
+        has_drift = true;
+
+        let file_name = file_path.to_string_lossy().replace('\\', "/");
+        let patch = diffy::create_patch(
+            current_content.as_deref().unwrap_or_default(),
+            expected_content.as_deref().unwrap_or_default(),
+        )
+        .to_string();
+
+        // 💡 replace the `--- original` and `+++ modified` header lines with the path of the file
+        let hunks = patch.splitn(3, '\n').nth(2).unwrap_or_default();
+        print!("--- a/{0}\n+++ b/{0}\n{1}", file_name, hunks);
+    }
+
+    if has_drift {
+        return Err(anyhow!(
+            "db folder is not up to date, run `ultime generate db` to regenerate it"
+        ));
+    }
+
+    logger::log(LogSource::Gen, "db folder is up to date");
 
     Ok(())
 }
 
-pub fn generate_db_folder() -> Result<()> {
+/// Generate the content of every file of the `db` module, without writing anything.
+fn generate_db_files(config: &UltimeConfig) -> Result<DbFiles> {
+    let mut db_modules = DbModules::default();
+    let mut models = BTreeMap::new();
+
+    let errors = generate_db_modules(config, &DbModule::ALL, &mut db_modules, &mut models)?;
+
+    Ok(DbFiles {
+        generated: db_modules.to_generated_files(&config.surrealdb)?,
+        models,
+        errors,
+    })
+}
+
+fn generate_db_modules(
+    config: &UltimeConfig,
+    modules: &[DbModule],
+    db_modules: &mut DbModules,
+    models: &mut BTreeMap<PathBuf, String>,
+) -> Result<ParseErrors> {
+    let folders = &config.folders;
+
+    let mut errors = ParseErrors::default();
+
+    let needs_tables = modules
+        .iter()
+        .any(|module| matches!(module, DbModule::Mutations | DbModule::Queries));
+    let mut table_errors = ParseErrors::default();
+    let tables = match needs_tables {
+        true => extract_table_definitions(&[&folders.schemas, &folders.events], &mut table_errors)?,
+        false => TableDefinitions::new(),
+    };
+
+    // 💡 the tables of broken files are missing, so the types would fall back to model stubs written in the user code
+    let can_infer_types = table_errors.is_empty();
+    errors.extend(table_errors);
+
+    for module in modules {
+        let mut module_errors = ParseErrors::default();
+
+        let files = match module {
+            DbModule::Mutations | DbModule::Queries if !can_infer_types => {
+                let files = keep_existing_module_files(*module, BTreeMap::new())?;
+                db_modules.0.insert(*module, files);
+                continue;
+            }
+            DbModule::Crud => generate_crud_files(folders, &mut module_errors)?,
+            DbModule::Events => generate_events_files(folders, &mut module_errors)?,
+            DbModule::Mutations => {
+                generate_mutations_files(folders, &tables, models, &mut module_errors)?
+            }
+            DbModule::Queries => {
+                generate_queries_files(folders, &tables, models, &mut module_errors)?
+            }
+        };
+
+        // 💡 keep the files generated from the broken source files until they can be parsed again
+        let files = match module_errors.is_empty() {
+            true => files,
+            false => keep_existing_module_files(*module, files)?,
+        };
+
+        db_modules.0.insert(*module, files);
+        errors.extend(module_errors);
+    }
+
+    Ok(errors)
+}
+
+/// Add the files of the module that exist on disk but are not generated anymore.
+fn keep_existing_module_files(
+    module: DbModule,
+    files: BTreeMap<PathBuf, String>,
+) -> Result<BTreeMap<PathBuf, String>> {
+    let db_dir = Path::new("src").join("db");
+    let module_dir = db_dir.join(module.name());
+
+    if !module_dir.exists() {
+        return Ok(files);
+    }
+
+    let mut items_to_keep: HashMap<String, String> = HashMap::new();
+
+    for file in module_dir.read_dir()? {
+        let file_path = file?.path();
+
+        if files.contains_key(&file_path)
+            || file_path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                != Some("rs")
+        {
+            continue;
+        }
+
+        let item_name = file_path.file_stem().unwrap().to_str().unwrap().to_string();
+        let content = std::fs::read_to_string(&file_path)?;
+
+        items_to_keep.insert(item_name, content);
+    }
+
+    let mut items_to_generate = files
+        .iter()
+        .filter(|(file_path, _)| file_path.parent() == Some(module_dir.as_path()))
+        .map(|(file_path, content)| {
+            let item_name = file_path.file_stem().unwrap().to_str().unwrap();
+            (item_name.to_string(), content.to_string())
+        })
+        .collect::<HashMap<_, _>>();
+    items_to_generate.extend(items_to_keep);
+
+    Ok(to_module_files(module, items_to_generate))
+}
+
+fn generate_queries_files(
+    folders: &FoldersConfig,
+    tables: &TableDefinitions,
+    models: &mut BTreeMap<PathBuf, String>,
+    errors: &mut ParseErrors,
+) -> Result<BTreeMap<PathBuf, String>> {
     let src_dir = Path::new("src");
 
-    // Generate queries
     let mut queries_to_generate: HashMap<String, String> = HashMap::new();
-    let mut has_queries_to_generate = false;
 
-    let queries_dir = Path::new("queries");
+    let queries_dir = Path::new(&folders.queries);
     if queries_dir.exists() {
         let queries_files = queries_dir.read_dir()?;
 
@@ -67,12 +416,21 @@ pub fn generate_db_folder() -> Result<()> {
             let query_file_path = query_file.path();
             let query_file_content = std::fs::read_to_string(&query_file_path)?;
 
-            let parsed_query = surrealdb::sql::parse(&query_file_content)?;
+            let parsed_query = match parse_surql_file(
+                &query_file_path,
+                &query_file_content,
+                &query_file_content,
+            ) {
+                Ok(parsed_query) => parsed_query,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
             let query_statements = parsed_query.0 .0;
 
-            let _is_multi_statements_query = query_statements.len() > 1;
-
             let variables = extract_query_variables(&query_file_content)?;
+            let result_mode = extract_query_result_mode(&query_file_content)?;
 
             let query_name = query_file_path
                 .file_stem()
@@ -83,45 +441,45 @@ pub fn generate_db_folder() -> Result<()> {
 
             let response_type = format!("{}_Query", query_name).to_case(Case::Pascal);
 
-            let content =
-                generate_from_query_template(query_name.to_string(), variables, response_type)?;
-
-            queries_to_generate.insert(query_name, content);
-        }
-
-        has_queries_to_generate = !queries_to_generate.is_empty();
-        if has_queries_to_generate {
-            let db_dir = src_dir.join("db");
-            ensures_folder_exists(&db_dir)?;
-
-            let queries_dir = db_dir.join("queries");
-            ensures_folder_exists(&queries_dir)?;
-
-            for (query_name, template) in &queries_to_generate {
-                let generated_query_file_name = format!("{}.rs", query_name);
-                let generated_query_file_path = queries_dir.join(generated_query_file_name);
-
-                std::fs::write(generated_query_file_path, template)?;
+            let results =
+                extract_query_results(&response_type, &query_statements, result_mode, tables);
+            for result in results.iter().filter(|result| result.structs.is_none()) {
+                add_model_stub(
+                    models,
+                    src_dir,
+                    "queries",
+                    &query_file_path,
+                    &result.response_type,
+                )?;
             }
 
-            let queries_mod_file_path = db_dir.join("queries.rs");
-
-            let queries_mod_file_content = queries_to_generate
-                .keys()
-                .sorted()
-                .map(|table_name| format!("pub mod {};", table_name))
-                .collect::<Vec<_>>()
-                .join("\n");
+            let content = generate_from_query_template(
+                folders.queries.to_string(),
+                query_name.to_string(),
+                variables,
+                response_type,
+                results,
+                result_mode == QueryResultMode::All,
+            )?;
 
-            std::fs::write(queries_mod_file_path, queries_mod_file_content)?;
+            queries_to_generate.insert(query_name, content);
         }
     }
 
-    // Generate mutations
+    Ok(to_module_files(DbModule::Queries, queries_to_generate))
+}
+
+fn generate_mutations_files(
+    folders: &FoldersConfig,
+    tables: &TableDefinitions,
+    models: &mut BTreeMap<PathBuf, String>,
+    errors: &mut ParseErrors,
+) -> Result<BTreeMap<PathBuf, String>> {
+    let src_dir = Path::new("src");
+
     let mut mutations_to_generate: HashMap<String, String> = HashMap::new();
-    let mut has_mutations_to_generate = false;
 
-    let mutations_dir = Path::new("mutations");
+    let mutations_dir = Path::new(&folders.mutations);
     if mutations_dir.exists() {
         let mutations_files = mutations_dir.read_dir()?;
 
@@ -130,12 +488,21 @@ pub fn generate_db_folder() -> Result<()> {
             let mutation_file_path = mutation_file.path();
             let mutation_file_content = std::fs::read_to_string(&mutation_file_path)?;
 
-            let parsed_query = surrealdb::sql::parse(&mutation_file_content)?;
+            let parsed_query = match parse_surql_file(
+                &mutation_file_path,
+                &mutation_file_content,
+                &mutation_file_content,
+            ) {
+                Ok(parsed_query) => parsed_query,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
             let mutation_statements = parsed_query.0 .0;
 
-            let _is_multi_statements_query = mutation_statements.len() > 1;
-
             let variables = extract_query_variables(&mutation_file_content)?;
+            let result_mode = extract_query_result_mode(&mutation_file_content)?;
 
             let mutation_name = mutation_file_path
                 .file_stem()
@@ -146,48 +513,41 @@ pub fn generate_db_folder() -> Result<()> {
 
             let response_type = format!("{}_Mutation", mutation_name).to_case(Case::Pascal);
 
+            let results =
+                extract_query_results(&response_type, &mutation_statements, result_mode, tables);
+            for result in results.iter().filter(|result| result.structs.is_none()) {
+                add_model_stub(
+                    models,
+                    src_dir,
+                    "mutations",
+                    &mutation_file_path,
+                    &result.response_type,
+                )?;
+            }
+
             let content = generate_from_mutation_template(
+                folders.mutations.to_string(),
                 mutation_name.to_string(),
                 variables,
                 response_type,
+                results,
+                result_mode == QueryResultMode::All,
             )?;
 
             mutations_to_generate.insert(mutation_name, content);
         }
-
-        has_mutations_to_generate = !mutations_to_generate.is_empty();
-        if has_mutations_to_generate {
-            let db_dir = src_dir.join("db");
-            ensures_folder_exists(&db_dir)?;
-
-            let mutations_dir = db_dir.join("mutations");
-            ensures_folder_exists(&mutations_dir)?;
-
-            for (mutation_name, template) in &mutations_to_generate {
-                let generated_mutation_file_name = format!("{}.rs", mutation_name);
-                let generated_mutation_file_path = mutations_dir.join(generated_mutation_file_name);
-
-                std::fs::write(generated_mutation_file_path, template)?;
-            }
-
-            let mutations_mod_file_path = db_dir.join("mutations.rs");
-
-            let mutations_mod_file_content = mutations_to_generate
-                .keys()
-                .sorted()
-                .map(|table_name| format!("pub mod {};", table_name))
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            std::fs::write(mutations_mod_file_path, mutations_mod_file_content)?;
-        }
     }
 
-    // Generate crud queries
+    Ok(to_module_files(DbModule::Mutations, mutations_to_generate))
+}
+
+fn generate_crud_files(
+    folders: &FoldersConfig,
+    errors: &mut ParseErrors,
+) -> Result<BTreeMap<PathBuf, String>> {
     let mut schemas_to_generate: HashMap<String, String> = HashMap::new();
-    let mut has_schemas_to_generate = false;
 
-    let schemas_dir = Path::new("schemas");
+    let schemas_dir = Path::new(&folders.schemas);
     if schemas_dir.exists() {
         let schemas_files = schemas_dir.read_dir()?;
 
@@ -195,8 +555,20 @@ pub fn generate_db_folder() -> Result<()> {
             let schema_file = schema_file?;
             let schema_file_path = schema_file.path();
             let schema_file_content = std::fs::read_to_string(&schema_file_path)?;
-
-            let parsed_schema = surrealdb::sql::parse(&schema_file_content)?;
+            let (stripped_file_content, optional_fields) =
+                strip_option_kinds(&schema_file_content)?;
+
+            let parsed_schema = match parse_surql_file(
+                &schema_file_path,
+                &schema_file_content,
+                &stripped_file_content,
+            ) {
+                Ok(parsed_schema) => parsed_schema,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
             let schema_statements = parsed_schema.0 .0;
 
             let define_table_statements =
@@ -220,51 +592,78 @@ pub fn generate_db_folder() -> Result<()> {
                     })
                     .collect::<Vec<_>>();
 
-                let struct_fields = extract_struct_fields(define_field_statements, true);
+                let (struct_fields, nested_structs) = extract_struct_fields(
+                    &struct_name,
+                    define_field_statements,
+                    &optional_fields,
+                    true,
+                );
 
                 let content = generate_from_crud_template(
                     table_name.to_string(),
                     struct_name,
                     struct_fields,
+                    nested_structs,
                 )?;
 
                 schemas_to_generate.insert(table_name, content);
             }
         }
+    }
 
-        has_schemas_to_generate = !schemas_to_generate.is_empty();
-        if has_schemas_to_generate {
-            let db_dir = src_dir.join("db");
-            ensures_folder_exists(&db_dir)?;
-
-            let crud_dir = db_dir.join("crud");
-            ensures_folder_exists(&crud_dir)?;
-
-            for (table_name, template) in &schemas_to_generate {
-                let generated_schema_file_name = format!("{}.rs", table_name);
-                let generated_schema_file_path = crud_dir.join(generated_schema_file_name);
+    Ok(to_module_files(DbModule::Crud, schemas_to_generate))
+}
 
-                std::fs::write(generated_schema_file_path, template)?;
-            }
+/// Fields of the struct generated in the `db::crud` module for a table, e.g. to scaffold the forms of a resource.
+pub fn get_crud_struct_fields(
+    schema_file_path: &Path,
+    table_name: &str,
+) -> Result<Vec<StructField>> {
+    let schema_file_content = std::fs::read_to_string(schema_file_path)?;
+    let (stripped_file_content, optional_fields) = strip_option_kinds(&schema_file_content)?;
+
+    let parsed_schema = parse_surql_file(
+        schema_file_path,
+        &schema_file_content,
+        &stripped_file_content,
+    )
+    .map_err(|error| anyhow!("{}", error))?;
+    let schema_statements = parsed_schema.0 .0;
+
+    let is_table_defined = extract_define_table_statements(schema_statements.clone())
+        .iter()
+        .any(|define_table_statement| define_table_statement.name.to_string() == table_name);
+    if !is_table_defined {
+        return Err(anyhow!(
+            "Table '{}' is not defined in {}",
+            table_name,
+            schema_file_path.display()
+        ));
+    }
 
-            let crud_mod_file_path = db_dir.join("crud.rs");
+    let define_field_statements = extract_define_field_statements(schema_statements)
+        .into_iter()
+        .filter(|define_field_statement| define_field_statement.what.to_string() == table_name)
+        .collect::<Vec<_>>();
 
-            let crud_mod_file_content = schemas_to_generate
-                .keys()
-                .sorted()
-                .map(|table_name| format!("pub mod {};", table_name))
-                .collect::<Vec<_>>()
-                .join("\n");
+    let struct_name = table_name.to_case(Case::Pascal);
+    let (struct_fields, _) = extract_struct_fields(
+        &struct_name,
+        define_field_statements,
+        &optional_fields,
+        true,
+    );
 
-            std::fs::write(crud_mod_file_path, crud_mod_file_content)?;
-        }
-    }
+    Ok(struct_fields)
+}
 
-    // Generate events
+fn generate_events_files(
+    folders: &FoldersConfig,
+    errors: &mut ParseErrors,
+) -> Result<BTreeMap<PathBuf, String>> {
     let mut events_to_generate: HashMap<String, String> = HashMap::new();
-    let mut has_events_to_generate = false;
 
-    let events_dir = Path::new("events");
+    let events_dir = Path::new(&folders.events);
     if events_dir.exists() {
         let events_files = events_dir.read_dir()?;
 
@@ -272,8 +671,20 @@ pub fn generate_db_folder() -> Result<()> {
             let event_files = event_files?;
             let event_files_path = event_files.path();
             let event_files_content = std::fs::read_to_string(&event_files_path)?;
-
-            let parsed_event = surrealdb::sql::parse(&event_files_content)?;
+            let (stripped_file_content, optional_fields) =
+                strip_option_kinds(&event_files_content)?;
+
+            let parsed_event = match parse_surql_file(
+                &event_files_path,
+                &event_files_content,
+                &stripped_file_content,
+            ) {
+                Ok(parsed_event) => parsed_event,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
             let event_statements = parsed_event.0 .0;
 
             let define_table_statements = extract_define_table_statements(event_statements.clone());
@@ -314,142 +725,474 @@ pub fn generate_db_folder() -> Result<()> {
                     })
                     .collect::<Vec<_>>();
 
-                let struct_fields = extract_struct_fields(define_field_statements, false);
+                let (struct_fields, nested_structs) = extract_struct_fields(
+                    &struct_name,
+                    define_field_statements,
+                    &optional_fields,
+                    false,
+                );
 
                 let content = generate_from_event_template(
                     func_name,
                     table_name.to_string(),
                     struct_name,
                     struct_fields,
+                    nested_structs,
                 )?;
 
                 events_to_generate.insert(table_name, content);
             }
         }
+    }
 
-        has_events_to_generate = !events_to_generate.is_empty();
-        if has_events_to_generate {
-            let db_dir = src_dir.join("db");
-            ensures_folder_exists(&db_dir)?;
+    Ok(to_module_files(DbModule::Events, events_to_generate))
+}
 
-            let events_dir = db_dir.join("events");
-            ensures_folder_exists(&events_dir)?;
+/// Files of a module: one file per generated item, declared in the `mod` file of the module.
+fn to_module_files(
+    module: DbModule,
+    items_to_generate: HashMap<String, String>,
+) -> BTreeMap<PathBuf, String> {
+    let mut files = BTreeMap::new();
 
-            for (table_name, template) in &events_to_generate {
-                let generated_events_file_name = format!("{}.rs", table_name);
-                let generated_events_file_path = events_dir.join(generated_events_file_name);
+    if items_to_generate.is_empty() {
+        return files;
+    }
 
-                std::fs::write(generated_events_file_path, template)?;
-            }
+    let db_dir = Path::new("src").join("db");
+    let module_dir = db_dir.join(module.name());
+
+    let mod_file_path = db_dir.join(format!("{}.rs", module.name()));
 
-            let events_mod_file_path = db_dir.join("events.rs");
+    let mod_file_content = items_to_generate
+        .keys()
+        .sorted()
+        .map(|item_name| format!("pub mod {};", item_name))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    files.insert(mod_file_path, mod_file_content);
+
+    for (item_name, content) in items_to_generate {
+        files.insert(module_dir.join(format!("{}.rs", item_name)), content);
+    }
 
-            let events_mod_file_content = events_to_generate
-                .keys()
-                .sorted()
-                .map(|table_name| format!("pub mod {};", table_name))
-                .collect::<Vec<_>>()
-                .join("\n");
+    files
+}
 
-            std::fs::write(events_mod_file_path, events_mod_file_content)?;
+fn write_model_files(models: &BTreeMap<PathBuf, String>) -> Result<()> {
+    for (model_file_path, model_file_content) in models {
+        if let Some(parent_dir) = model_file_path.parent() {
+            ensures_folder_exists(parent_dir)?;
         }
+        std::fs::write(model_file_path, model_file_content)?;
     }
 
-    // Generate db.rs
-    let has_db_changes = has_queries_to_generate
-        || has_mutations_to_generate
-        || has_schemas_to_generate
-        || has_events_to_generate;
-    if has_db_changes {
-        let mod_file_path = src_dir.join("db.rs");
-        let mut mod_file_modules = vec![];
+    Ok(())
+}
+
+fn print_db_changes(changes: &DbChanges) {
+    if changes.is_empty() {
+        logger::log(LogSource::Gen, "db folder up to date");
+        return;
+    }
+
+    logger::log(LogSource::Gen, "db folder generated...");
 
-        if has_schemas_to_generate {
-            mod_file_modules.push("crud");
+    for module in &changes.added {
+        logger::log(LogSource::Gen, format_args!("  + {}", module));
+    }
+    for module in &changes.changed {
+        logger::log(LogSource::Gen, format_args!("  ~ {}", module));
+    }
+    for module in &changes.removed {
+        logger::log(LogSource::Gen, format_args!("  - {}", module));
+    }
+}
+
+/// Generate the db folder, then regenerate the modules affected by each change of the watched folders.
+/// Broken .surql files are reported instead of failing, so that they can be fixed while watching.
+pub fn watch_to_regenerate_db_folder(
+    config: &UltimeConfig,
+    status: &SharedGenerationStatus,
+) -> Result<RecommendedWatcher> {
+    let folders = &config.folders;
+
+    // 💡 keep the generated modules in memory to only regenerate the affected ones on changes
+    let mut db_modules = DbModules::default();
+    if let Err(error) = regenerate_db_modules(config, &DbModule::ALL, &mut db_modules, status) {
+        logger::log_error(
+            LogSource::Gen,
+            format_args!("Error while generating db folder: {:#}", error),
+        );
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(sender)?;
+
+    let folders_to_watch = [
+        (
+            "schemas",
+            &folders.schemas,
+            vec![DbModule::Crud, DbModule::Mutations, DbModule::Queries],
+        ),
+        (
+            "events",
+            &folders.events,
+            vec![DbModule::Events, DbModule::Mutations, DbModule::Queries],
+        ),
+        ("queries", &folders.queries, vec![DbModule::Queries]),
+        ("mutations", &folders.mutations, vec![DbModule::Mutations]),
+    ];
+
+    let mut watched_dirs = vec![];
+
+    for (name, folder, modules) in folders_to_watch {
+        let dir = Path::new(folder);
+        if dir.exists() {
+            logger::log(LogSource::Gen, format_args!("Watching {} folder...", name));
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+            watched_dirs.push((dir.canonicalize()?, modules));
         }
-        if has_events_to_generate {
-            mod_file_modules.push("events");
+    }
+
+    let config = config.clone();
+    let status = status.clone();
+    std::thread::spawn(move || {
+        regenerate_on_changes(&config, &watched_dirs, db_modules, &status, receiver)
+    });
+
+    Ok(watcher)
+}
+
+/// Wait for the end of a burst of file events, then only regenerate the modules affected by the changed files.
+/// The thread ends when the watcher is dropped.
+fn regenerate_on_changes(
+    config: &UltimeConfig,
+    watched_dirs: &[(PathBuf, Vec<DbModule>)],
+    mut db_modules: DbModules,
+    status: &SharedGenerationStatus,
+    receiver: Receiver<notify::Result<notify::Event>>,
+) {
+    while let Ok(result) = receiver.recv() {
+        let mut results = vec![result];
+        while let Ok(result) = receiver.recv_timeout(WATCH_DEBOUNCE_DURATION) {
+            results.push(result);
         }
-        if has_mutations_to_generate {
-            mod_file_modules.push("mutations");
+
+        let changed_file_paths = results
+            .into_iter()
+            .filter_map(|result| match result {
+                Ok(event) => Some(event),
+                Err(error) => {
+                    logger::log_error(LogSource::Gen, format_args!("Watch error: {}", error));
+                    None
+                }
+            })
+            .filter(|event| is_file_change(&event.kind))
+            .flat_map(|event| event.paths)
+            .collect::<Vec<_>>();
+
+        let modules = get_affected_modules(watched_dirs, &changed_file_paths);
+        if modules.is_empty() {
+            continue;
         }
-        if has_queries_to_generate {
-            mod_file_modules.push("queries");
+
+        if let Err(error) = regenerate_db_modules(config, &modules, &mut db_modules, status) {
+            logger::log_error(
+                LogSource::Gen,
+                format_args!("Error while generating db folder: {:#}", error),
+            );
         }
+    }
+}
 
-        let mod_file_content = mod_file_modules
-            .iter()
-            .map(|module| format!("pub mod {};", module))
-            .collect::<Vec<_>>()
-            .join("\n");
+/// Regenerate the given modules and record the result in the shared status.
+fn regenerate_db_modules(
+    config: &UltimeConfig,
+    modules: &[DbModule],
+    db_modules: &mut DbModules,
+    status: &SharedGenerationStatus,
+) -> Result<()> {
+    let result = apply_db_modules(config, modules, db_modules);
+    *status.lock().unwrap() = Some(GenerationStatus::new(&result));
+
+    result.map(|_| ())
+}
+
+fn apply_db_modules(
+    config: &UltimeConfig,
+    modules: &[DbModule],
+    db_modules: &mut DbModules,
+) -> Result<DbChanges> {
+    let mut models = BTreeMap::new();
+
+    let errors = generate_db_modules(config, modules, db_modules, &mut models)?;
+    write_model_files(&models)?;
 
-        std::fs::write(mod_file_path, mod_file_content)?;
+    let changes =
+        manifest::apply_generated_files(&db_modules.to_generated_files(&config.surrealdb)?)?;
+    print_db_changes(&changes);
 
-        println!("db folder generated...");
-    } else {
-        // TODO : Remove db.rs if it exists
+    match errors.is_empty() {
+        true => Ok(changes),
+        false => Err(errors.report()),
     }
+}
 
-    Ok(())
+pub fn is_file_change(event_kind: &EventKind) -> bool {
+    matches!(
+        event_kind,
+        EventKind::Create(_)
+            | EventKind::Remove(_)
+            | EventKind::Modify(ModifyKind::Any | ModifyKind::Data(_) | ModifyKind::Name(_))
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    )
 }
 
-pub fn watch_to_regenerate_db_folder() -> Result<RecommendedWatcher> {
-    fn watch_event(result: notify::Result<notify::Event>) {
-        match result {
-            Ok(event) => {
-                if let EventKind::Access(AccessKind::Close(AccessMode::Write)) = event.kind {
-                    let _paths = event.paths;
-                    let result = generate_db_folder();
-
-                    match result {
-                        Ok(_) => {
-                            println!("db folder generated...");
-                        }
-                        Err(error) => {
-                            eprintln!("Error while generating db folder: {:?}", error);
-                        }
-                    }
-                }
-            }
-            Err(e) => println!("Watch error: {:?}", e),
+fn get_affected_modules(
+    watched_dirs: &[(PathBuf, Vec<DbModule>)],
+    file_paths: &[PathBuf],
+) -> Vec<DbModule> {
+    file_paths
+        .iter()
+        .filter(|file_path| {
+            file_path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                == Some("surql")
+        })
+        .filter_map(|file_path| file_path.parent()?.canonicalize().ok())
+        .flat_map(|dir| {
+            watched_dirs
+                .iter()
+                .filter(move |(watched_dir, _)| *watched_dir == dir)
+                .flat_map(|(_, modules)| modules.iter().copied())
+        })
+        .unique()
+        .sorted()
+        .collect()
+}
+
+/// Index of the response of each statement, ignoring the statements that do not return any response.
+/// Only the statements that return data are kept (e.g. `LET` statements are skipped).
+fn get_returning_statement_indexes(statements: &[Statement]) -> Vec<(usize, &Statement)> {
+    statements
+        .iter()
+        .filter(|statement| {
+            !matches!(
+                statement,
+                Statement::Begin(_)
+                    | Statement::Cancel(_)
+                    | Statement::Commit(_)
+                    | Statement::Option(_)
+            )
+        })
+        .enumerate()
+        .filter(|(_, statement)| {
+            !matches!(
+                statement,
+                Statement::Set(_)
+                    | Statement::Use(_)
+                    | Statement::Define(_)
+                    | Statement::Remove(_)
+                    | Statement::Kill(_)
+                    | Statement::Sleep(_)
+            )
+        })
+        .collect()
+}
+
+fn extract_query_results(
+    response_type: &str,
+    statements: &[Statement],
+    result_mode: QueryResultMode,
+    tables: &TableDefinitions,
+) -> Vec<QueryResult> {
+    let returning_statements = get_returning_statement_indexes(statements);
+
+    match result_mode {
+        QueryResultMode::Last => {
+            let (index, structs) = match returning_statements.last() {
+                Some((index, statement)) => (
+                    *index,
+                    infer_response_structs(response_type, statement, tables),
+                ),
+                None => (0, None),
+            };
+
+            vec![QueryResult {
+                index,
+                response_type: response_type.to_string(),
+                structs,
+            }]
         }
+        QueryResultMode::All => returning_statements
+            .into_iter()
+            .enumerate()
+            .map(|(position, (index, statement))| {
+                let response_type = format!("{}Result{}", response_type, position);
+                let structs = infer_response_structs(&response_type, statement, tables);
+
+                QueryResult {
+                    index,
+                    response_type,
+                    structs,
+                }
+            })
+            .collect(),
     }
+}
 
-    let mut watcher: RecommendedWatcher = notify::recommended_watcher(watch_event)?;
+fn extract_table_definitions(
+    folders: &[&String],
+    errors: &mut ParseErrors,
+) -> Result<TableDefinitions> {
+    let mut tables = TableDefinitions::new();
 
-    let schemas_dir = Path::new("schemas");
-    if schemas_dir.exists() {
-        println!("Watching schemas folder...");
-        watcher.watch(schemas_dir, RecursiveMode::NonRecursive)?;
+    for folder in folders {
+        let dir = Path::new(folder);
+        if !dir.exists() {
+            continue;
+        }
+
+        for file in dir.read_dir()? {
+            let file_path = file?.path();
+            let file_content = std::fs::read_to_string(&file_path)?;
+            let (stripped_file_content, optional_fields) = strip_option_kinds(&file_content)?;
+
+            // 💡 the error is reported with the module generated from this file
+            let parsed_file =
+                match parse_surql_file(&file_path, &file_content, &stripped_file_content) {
+                    Ok(parsed_file) => parsed_file,
+                    Err(error) => {
+                        errors.push(error);
+                        continue;
+                    }
+                };
+
+            add_table_definitions(&mut tables, parsed_file.0 .0, optional_fields);
+        }
     }
 
-    let events_dir = Path::new("events");
-    if events_dir.exists() {
-        println!("Watching events folder...");
-        watcher.watch(events_dir, RecursiveMode::NonRecursive)?;
+    Ok(tables)
+}
+
+fn add_table_definitions(
+    tables: &mut TableDefinitions,
+    statements: Vec<Statement>,
+    optional_fields: Vec<OptionalField>,
+) {
+    let define_table_statements = extract_define_table_statements(statements.clone());
+    let define_field_statements = extract_define_field_statements(statements);
+
+    for define_table_statement in define_table_statements {
+        let table_name = define_table_statement.name.to_string();
+        let table_definition = tables.entry(table_name.to_string()).or_default();
+
+        table_definition.define_field_statements.extend(
+            define_field_statements
+                .iter()
+                .filter(|define_field_statement| {
+                    define_field_statement.what.to_string() == table_name
+                })
+                .cloned(),
+        );
+        table_definition.optional_fields.extend(
+            optional_fields
+                .iter()
+                .filter(|optional_field| optional_field.table == table_name)
+                .cloned(),
+        );
     }
+}
 
-    let queries_dir = Path::new("queries");
-    if queries_dir.exists() {
-        println!("Watching queries folder...");
-        watcher.watch(queries_dir, RecursiveMode::NonRecursive)?;
+/// When the response type cannot be inferred, it should be written by hand in the `models` folder.
+/// A stub is added the first time so that the generated code still compiles.
+fn add_model_stub(
+    models: &mut BTreeMap<PathBuf, String>,
+    src_dir: &Path,
+    module_name: &str,
+    file_path: &Path,
+    response_type: &str,
+) -> Result<()> {
+    let model_file_path = src_dir.join("models").join(format!("{}.rs", module_name));
+    let model_file_content = read_model_file(models, &model_file_path)?;
+
+    let type_regex = Regex::new(&format!(r#"\b(?:type|struct|enum)\s+{}\b"#, response_type))?;
+    if type_regex.is_match(&model_file_content) {
+        return Ok(());
     }
 
-    let mutations_dir = Path::new("mutations");
-    if mutations_dir.exists() {
-        println!("Watching mutations folder...");
-        watcher.watch(mutations_dir, RecursiveMode::NonRecursive)?;
+    let mut content = match model_file_content.is_empty() {
+        true => "use serde::{Deserialize, Serialize};\n".to_string(),
+        false => model_file_content,
+    };
+
+    let file_name = file_path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .unwrap_or_default();
+
+    content.push_str(&format!(
+        r#"
+// TODO : Define the properties returned by `{module_name}/{file_name}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct {response_type}Item {{}}
+
+pub type {response_type} = Vec<{response_type}Item>;
+"#
+    ));
+
+    models.insert(model_file_path, content);
+
+    let models_mod_file_path = src_dir.join("models.rs");
+    let mut models_mod_file_content = read_model_file(models, &models_mod_file_path)?;
+
+    let mod_declaration = format!("pub mod {};", module_name);
+    if !models_mod_file_content
+        .lines()
+        .any(|line| line.trim() == mod_declaration)
+    {
+        if !models_mod_file_content.is_empty() && !models_mod_file_content.ends_with('\n') {
+            models_mod_file_content.push('\n');
+        }
+        models_mod_file_content.push_str(&mod_declaration);
+        models_mod_file_content.push('\n');
+
+        models.insert(models_mod_file_path, models_mod_file_content);
     }
 
-    Ok(watcher)
+    Ok(())
 }
 
-fn ensures_folder_exists(dir_path: &PathBuf) -> Result<()> {
-    if !dir_path.exists() {
-        fs_extra::dir::create_all(dir_path, false)?;
+/// Latest content of a model file, including the stubs that are not written yet.
+fn read_model_file(models: &BTreeMap<PathBuf, String>, file_path: &Path) -> Result<String> {
+    if let Some(content) = models.get(file_path) {
+        return Ok(content.to_string());
     }
 
-    Ok(())
+    match file_path.exists() {
+        true => Ok(std::fs::read_to_string(file_path)?),
+        false => Ok(String::new()),
+    }
+}
+
+fn uses_thing_type(structs: &[&NestedStruct]) -> bool {
+    let thing_type = get_output_type(&SurrealType::Id);
+
+    structs.iter().any(|nested_struct| {
+        nested_struct
+            .fields
+            .iter()
+            .flat_map(|field| {
+                field
+                    .type_str
+                    .split(|c: char| !c.is_alphanumeric() && c != '_')
+            })
+            .any(|type_name| type_name == thing_type)
+    })
 }
 
 fn extract_define_table_statements(statements: Vec<Statement>) -> Vec<DefineTableStatement> {
@@ -535,85 +1278,246 @@ fn is_value_param_used(value_statement: Option<Value>) -> bool {
 }
 
 fn extract_struct_fields(
+    struct_name: &str,
     define_field_statements: Vec<DefineFieldStatement>,
+    optional_fields: &[OptionalField],
     with_id: bool,
-) -> Vec<StructField> {
-    let mut struct_fields: HashMap<String, SurrealType> = HashMap::new();
+) -> (Vec<StructField>, Vec<NestedStruct>) {
+    let mut nested_structs = vec![];
+
+    let mut struct_fields = extract_object_fields(
+        struct_name,
+        "",
+        &define_field_statements,
+        optional_fields,
+        &mut nested_structs,
+    );
 
     if with_id {
-        struct_fields.insert("id".to_string(), SurrealType::Id);
+        struct_fields.retain(|struct_field| struct_field.name != "id");
+        struct_fields.push(StructField {
+            name: "id".to_string(),
+            type_str: get_output_type(&SurrealType::Id),
+            is_optional: false,
+        });
+        struct_fields.sort_by_key(|struct_field| struct_field.name.to_string());
     }
 
-    for define_field_statement in &define_field_statements {
-        let field_name = define_field_statement.name.to_string();
+    (struct_fields, nested_structs)
+}
 
-        if field_name.contains("[*]") {
-            continue;
-        }
+/// Extract the direct fields of an object, based on the path prefix of each field.
+/// e.g. `settings.theme` is a field of the `settings` object.
+fn extract_object_fields(
+    struct_name: &str,
+    prefix: &str,
+    define_field_statements: &[DefineFieldStatement],
+    optional_fields: &[OptionalField],
+    nested_structs: &mut Vec<NestedStruct>,
+) -> Vec<StructField> {
+    let field_names = define_field_statements
+        .iter()
+        .filter_map(|define_field_statement| {
+            let field_path = define_field_statement.name.to_string();
+            let field_name = field_path
+                .strip_prefix(prefix)?
+                .split(['.', '['])
+                .next()
+                .unwrap_or_default()
+                .to_string();
 
-        let field_type = get_surreal_type(
-            define_field_statement,
-            &field_name,
-            &define_field_statements,
-        );
+            Some(field_name)
+        })
+        .filter(|field_name| !field_name.is_empty())
+        .unique()
+        .sorted()
+        .collect::<Vec<_>>();
 
-        struct_fields.insert(field_name, field_type);
-    }
+    field_names
+        .into_iter()
+        .map(|field_name| {
+            let field_path = format!("{}{}", prefix, field_name);
 
-    let struct_fields = struct_fields
-        .iter()
-        .sorted_by_key(|(field_name, _)| field_name.to_string())
-        .map(|(field_name, field_type)| {
-            let type_str = get_output_type(field_type);
+            let define_field_statement = define_field_statements
+                .iter()
+                .find(|statement| statement.name.to_string() == field_path);
+
+            let field_type = get_surreal_type(
+                struct_name,
+                &field_name,
+                &field_path,
+                define_field_statement.and_then(|statement| statement.kind.as_ref()),
+                define_field_statements,
+                optional_fields,
+                nested_structs,
+            );
+
+            let is_optional = match define_field_statement {
+                Some(define_field_statement) => {
+                    is_optional_struct_field(define_field_statement, optional_fields)
+                }
+                None => true,
+            };
+
+            let type_str = get_output_type(&field_type);
+            let type_str = match is_optional {
+                true => format!("Option<{}>", type_str),
+                false => type_str,
+            };
 
             StructField {
-                name: field_name.to_string(),
+                name: field_name,
                 type_str,
+                is_optional,
             }
         })
-        .collect::<Vec<_>>();
-    struct_fields
+        .collect::<Vec<_>>()
+}
+
+fn is_optional_struct_field(
+    define_field_statement: &DefineFieldStatement,
+    optional_fields: &[OptionalField],
+) -> bool {
+    let is_option_kind = optional_fields.iter().any(|optional_field| {
+        optional_field.table == define_field_statement.what.to_string()
+            && optional_field.field == define_field_statement.name.to_string()
+    });
+
+    is_option_kind || is_optional_field(define_field_statement)
+}
+
+/// A field can be NONE (and so should be omitted when sent to the database) when:
+/// * its value is computed by the database, like `VALUE $before OR time::now()`
+/// * or there is no `ASSERT` that prevents `$value` to be NONE
+fn is_optional_field(define_field_statement: &DefineFieldStatement) -> bool {
+    if let Some(value) = &define_field_statement.value {
+        if has_default_value(value) {
+            return true;
+        }
+    }
+
+    match &define_field_statement.assert {
+        Some(assert) => is_none_value_allowed(assert),
+        None => true,
+    }
+}
+
+fn has_default_value(value: &Value) -> bool {
+    match value {
+        Value::Expression(expr) if matches!(expr.o, Operator::Or | Operator::Nco) => true,
+        value => !is_value_param_used(Some(value.clone())),
+    }
+}
+
+fn is_none_value_allowed(assert: &Value) -> bool {
+    match assert {
+        Value::Expression(expr) => match expr.o {
+            Operator::Or | Operator::Nco => {
+                is_none_value_allowed(&expr.l) || is_none_value_allowed(&expr.r)
+            }
+            Operator::Equal | Operator::Exact => {
+                let is_none = |value: &Value| matches!(value, Value::None | Value::Null);
+                let is_value_param =
+                    |value: &Value| matches!(value, Value::Param(param) if param.0 .0 == "value");
+
+                (is_value_param(&expr.l) && is_none(&expr.r))
+                    || (is_none(&expr.l) && is_value_param(&expr.r))
+            }
+            _ => false,
+        },
+        _ => false,
+    }
 }
 
 fn get_surreal_type(
-    current_define_field_statement: &DefineFieldStatement,
-    field_name: &String,
+    struct_name: &str,
+    field_name: &str,
+    field_path: &str,
+    kind: Option<&Kind>,
     define_field_statements: &[DefineFieldStatement],
+    optional_fields: &[OptionalField],
+    nested_structs: &mut Vec<NestedStruct>,
 ) -> SurrealType {
-    // TODO : Handle other field types
+    let nested_prefix = format!("{}.", field_path);
+    let has_nested_fields = define_field_statements
+        .iter()
+        .any(|statement| statement.name.to_string().starts_with(&nested_prefix));
 
-    match current_define_field_statement.kind {
+    if has_nested_fields && matches!(kind, None | Some(Kind::Object) | Some(Kind::Any)) {
+        let nested_struct_name = format!("{}_{}", struct_name, field_name).to_case(Case::Pascal);
+
+        let fields = extract_object_fields(
+            &nested_struct_name,
+            &nested_prefix,
+            define_field_statements,
+            optional_fields,
+            nested_structs,
+        );
+
+        nested_structs.push(NestedStruct {
+            name: nested_struct_name.to_string(),
+            fields,
+        });
+
+        return SurrealType::Struct(nested_struct_name);
+    }
+
+    match kind {
+        Some(Kind::Any) => SurrealType::Any,
+        Some(Kind::Bool) => SurrealType::Bool,
+        Some(Kind::Bytes) => SurrealType::Bytes,
+        Some(Kind::Datetime) => SurrealType::Datetime,
+        Some(Kind::Decimal) => SurrealType::Decimal,
+        Some(Kind::Duration) => SurrealType::Duration,
+        Some(Kind::Float) => SurrealType::Float,
+        Some(Kind::Int) => SurrealType::Int,
+        Some(Kind::Number) => SurrealType::Number,
+        Some(Kind::Object) => SurrealType::Object,
         Some(Kind::String) => SurrealType::String,
+        Some(Kind::Geometry(_)) => SurrealType::Geometry,
         Some(Kind::Record(_)) => SurrealType::Id,
         Some(Kind::Array) => {
-            let inner_field_name = format!("{}[*]", field_name);
+            let inner_field_path = format!("{}[*]", field_path);
 
-            let inner_field_statement = &define_field_statements
+            let inner_field_statement = define_field_statements
                 .iter()
-                .find(|statement| statement.name.to_string() == inner_field_name);
-
-            match inner_field_statement {
-                Some(inner_field_statement) => {
-                    let inner_type = get_surreal_type(
-                        inner_field_statement,
-                        &inner_field_name,
-                        define_field_statements,
-                    );
-                    SurrealType::Array(Box::new(inner_type))
-                }
-                None => SurrealType::Array(Box::new(SurrealType::Unknown)),
-            }
+                .find(|statement| statement.name.to_string() == inner_field_path);
+
+            let inner_type = get_surreal_type(
+                struct_name,
+                field_name,
+                &inner_field_path,
+                inner_field_statement.and_then(|statement| statement.kind.as_ref()),
+                define_field_statements,
+                optional_fields,
+                nested_structs,
+            );
+
+            SurrealType::Array(Box::new(inner_type))
         }
-        _ => SurrealType::Unknown,
+        None => SurrealType::Unknown,
     }
 }
 
 fn get_output_type(field_type: &SurrealType) -> String {
     match field_type {
         SurrealType::Id => "Thing".to_string(),
+        SurrealType::Bool => "bool".to_string(),
+        SurrealType::Bytes => "Vec<u8>".to_string(),
+        SurrealType::Datetime => "chrono::DateTime<chrono::Utc>".to_string(),
+        SurrealType::Decimal => "rust_decimal::Decimal".to_string(),
+        SurrealType::Duration => "surrealdb::sql::Duration".to_string(),
+        SurrealType::Float => "f64".to_string(),
+        SurrealType::Int => "i64".to_string(),
+        SurrealType::Number => "f64".to_string(),
         SurrealType::String => "String".to_string(),
+        SurrealType::Geometry => "surrealdb::sql::Geometry".to_string(),
+        SurrealType::Struct(struct_name) => struct_name.to_string(),
         SurrealType::Array(nested_type) => format!("Vec<{}>", get_output_type(nested_type)),
-        SurrealType::Unknown => "String".to_string(), // TODO : What to do here?
+        // 💡 no type information available, so keep the raw JSON value
+        SurrealType::Any | SurrealType::Object | SurrealType::Unknown => {
+            "serde_json::Value".to_string()
+        }
     }
 }
 
@@ -621,6 +1525,7 @@ fn generate_from_crud_template(
     table_name: String,
     struct_name: String,
     struct_fields: Vec<StructField>,
+    nested_structs: Vec<NestedStruct>,
 ) -> Result<String> {
     const TEMPLATES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/generate");
 
@@ -633,7 +1538,7 @@ fn generate_from_crud_template(
 
     let content = Environment::new().render_str(
         &template_content,
-        context! { table_name, struct_name, struct_fields },
+        context! { table_name, struct_name, struct_fields, nested_structs },
     )?;
 
     Ok(content)
@@ -644,6 +1549,7 @@ fn generate_from_event_template(
     table_name: String,
     struct_name: String,
     struct_fields: Vec<StructField>,
+    nested_structs: Vec<NestedStruct>,
 ) -> Result<String> {
     const TEMPLATES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/generate");
 
@@ -656,19 +1562,30 @@ fn generate_from_event_template(
 
     let content = Environment::new().render_str(
         &template_content,
-        context! { func_name, table_name, struct_name, struct_fields },
+        context! { func_name, table_name, struct_name, struct_fields, nested_structs },
     )?;
 
     Ok(content)
 }
 
 fn generate_from_query_template(
+    folder: String,
     file_name: String,
     variables: Vec<QueryVariable>,
     response_type: String,
+    results: Vec<QueryResult>,
+    is_tuple: bool,
 ) -> Result<String> {
     const TEMPLATES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/generate");
 
+    let structs = results
+        .iter()
+        .filter_map(|result| result.structs.as_ref())
+        .flatten()
+        .collect::<Vec<_>>();
+    let has_structs = !structs.is_empty();
+    let uses_thing = uses_thing_type(&structs);
+
     let template_content = TEMPLATES_DIR
         .get_file("query.rs.jinja2")
         .context("Cannot get template 'query.rs.jinja2'")?
@@ -678,19 +1595,30 @@ fn generate_from_query_template(
 
     let content = Environment::new().render_str(
         &template_content,
-        context! { file_name, variables, response_type },
+        context! { folder, file_name, variables, response_type, results, is_tuple, has_structs, uses_thing },
     )?;
 
     Ok(content)
 }
 
 fn generate_from_mutation_template(
+    folder: String,
     file_name: String,
     variables: Vec<QueryVariable>,
     response_type: String,
+    results: Vec<QueryResult>,
+    is_tuple: bool,
 ) -> Result<String> {
     const TEMPLATES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/generate");
 
+    let structs = results
+        .iter()
+        .filter_map(|result| result.structs.as_ref())
+        .flatten()
+        .collect::<Vec<_>>();
+    let has_structs = !structs.is_empty();
+    let uses_thing = uses_thing_type(&structs);
+
     let template_content = TEMPLATES_DIR
         .get_file("mutation.rs.jinja2")
         .context("Cannot get template 'mutation.rs.jinja2'")?
@@ -700,12 +1628,45 @@ fn generate_from_mutation_template(
 
     let content = Environment::new().render_str(
         &template_content,
-        context! { file_name, variables, response_type },
+        context! { folder, file_name, variables, response_type, results, is_tuple, has_structs, uses_thing },
     )?;
 
     Ok(content)
 }
 
+fn generate_from_connection_template(surrealdb: &SurrealdbConfig) -> Result<String> {
+    const TEMPLATES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/generate");
+
+    let template_content = TEMPLATES_DIR
+        .get_file("connection.rs.jinja2")
+        .context("Cannot get template 'connection.rs.jinja2'")?
+        .contents_utf8()
+        .context("Cannot get template 'connection.rs.jinja2'")?
+        .to_string();
+
+    let content = Environment::new().render_str(
+        &template_content,
+        context! { surrealdb, address_env => SURREALDB_ADDRESS_ENV },
+    )?;
+
+    Ok(content)
+}
+
+fn generate_from_db_mod_template(modules: &[&str]) -> Result<String> {
+    const TEMPLATES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/generate");
+
+    let template_content = TEMPLATES_DIR
+        .get_file("db.rs.jinja2")
+        .context("Cannot get template 'db.rs.jinja2'")?
+        .contents_utf8()
+        .context("Cannot get template 'db.rs.jinja2'")?
+        .to_string();
+
+    let content = Environment::new().render_str(&template_content, context! { modules })?;
+
+    Ok(content)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -719,14 +1680,17 @@ mod tests {
             StructField {
                 name: "id".to_string(),
                 type_str: "Thing".to_string(),
+                is_optional: false,
             },
             StructField {
                 name: "title".to_string(),
                 type_str: "String".to_string(),
+                is_optional: false,
             },
             StructField {
                 name: "content".to_string(),
                 type_str: "String".to_string(),
+                is_optional: false,
             },
         ];
 
@@ -734,6 +1698,7 @@ mod tests {
             table_name.to_string(),
             struct_name.to_string(),
             struct_fields,
+            vec![],
         )
         .unwrap();
 
@@ -794,14 +1759,17 @@ pub async fn delete_post<C: Connection>(db: &'_ Surreal<C>, id: &str) -> Result<
             StructField {
                 name: "id".to_string(),
                 type_str: "Thing".to_string(),
+                is_optional: false,
             },
             StructField {
                 name: "script_name".to_string(),
                 type_str: "String".to_string(),
+                is_optional: false,
             },
             StructField {
                 name: "executed_at".to_string(),
                 type_str: "String".to_string(),
+                is_optional: false,
             },
         ];
 
@@ -809,6 +1777,7 @@ pub async fn delete_post<C: Connection>(db: &'_ Surreal<C>, id: &str) -> Result<
             table_name.to_string(),
             struct_name.to_string(),
             struct_fields,
+            vec![],
         )
         .unwrap();
 
@@ -849,6 +1818,7 @@ pub async fn find_script_migration<C: Connection>(db: &'_ Surreal<C>, id: &str)
         let struct_fields = vec![StructField {
             name: "post_id".to_string(),
             type_str: "Thing".to_string(),
+            is_optional: false,
         }];
 
         let result = generate_from_event_template(
@@ -856,6 +1826,7 @@ pub async fn find_script_migration<C: Connection>(db: &'_ Surreal<C>, id: &str)
             table_name.to_string(),
             struct_name.to_string(),
             struct_fields,
+            vec![],
         )
         .unwrap();
 
@@ -876,6 +1847,45 @@ pub async fn publish_post<C: Connection>(db: &'_ Surreal<C>, data: PublishPostDa
         );
     }
 
+    #[test]
+    fn generate_crud_content_with_optional_fields() {
+        let table_name = "permission";
+        let struct_name = "Permission";
+        let struct_fields = vec![
+            StructField {
+                name: "id".to_string(),
+                type_str: "Thing".to_string(),
+                is_optional: false,
+            },
+            StructField {
+                name: "name".to_string(),
+                type_str: "Option<String>".to_string(),
+                is_optional: true,
+            },
+        ];
+
+        let result = generate_from_crud_template(
+            table_name.to_string(),
+            struct_name.to_string(),
+            struct_fields,
+            vec![],
+        )
+        .unwrap();
+
+        assert!(result.starts_with(
+            "use serde::{Deserialize, Serialize};
+use surrealdb::{sql::Thing, Connection, Result, Surreal};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Permission {
+    pub id: Thing,
+    #[serde(skip_serializing_if = \"Option::is_none\")]
+    pub name: Option<String>,
+}
+"
+        ));
+    }
+
     #[test]
     fn generate_posts_query_content() {
         let file_name = "posts";
@@ -883,9 +1893,16 @@ pub async fn publish_post<C: Connection>(db: &'_ Surreal<C>, data: PublishPostDa
         let response_type = "PostsQuery";
 
         let result = generate_from_query_template(
+            "queries".to_string(),
             file_name.to_string(),
             variables,
             response_type.to_string(),
+            vec![QueryResult {
+                index: 0,
+                response_type: response_type.to_string(),
+                structs: None,
+            }],
+            false,
         )
         .unwrap();
 
@@ -893,7 +1910,71 @@ pub async fn publish_post<C: Connection>(db: &'_ Surreal<C>, data: PublishPostDa
             result,
             "use surrealdb::{Surreal, Connection, Result};
 
-use crate::models::queries::PostsQuery;
+pub use crate::models::queries::PostsQuery;
+
+pub async fn query_posts<C: Connection>(
+    db: &'_ Surreal<C>
+) -> Result<PostsQuery> {
+    const QUERY: &str = include_str!(\"../../../queries/posts.surql\");
+
+    let result: PostsQuery = db
+        .query(QUERY)
+        .await?
+        .take(0)?;
+
+    Ok(result)
+}"
+        );
+    }
+
+    #[test]
+    fn generate_posts_query_content_with_inferred_structs() {
+        let file_name = "posts";
+        let variables = vec![];
+        let response_type = "PostsQuery";
+        let structs = vec![NestedStruct {
+            name: "PostsQueryItem".to_string(),
+            fields: vec![
+                StructField {
+                    name: "id".to_string(),
+                    type_str: "Thing".to_string(),
+                    is_optional: false,
+                },
+                StructField {
+                    name: "title".to_string(),
+                    type_str: "Option<String>".to_string(),
+                    is_optional: true,
+                },
+            ],
+        }];
+
+        let result = generate_from_query_template(
+            "queries".to_string(),
+            file_name.to_string(),
+            variables,
+            response_type.to_string(),
+            vec![QueryResult {
+                index: 0,
+                response_type: response_type.to_string(),
+                structs: Some(structs),
+            }],
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            "use serde::{Deserialize, Serialize};
+use surrealdb::{sql::Thing, Surreal, Connection, Result};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostsQueryItem {
+    pub id: Thing,
+    #[serde(skip_serializing_if = \"Option::is_none\")]
+    pub title: Option<String>,
+}
+
+pub type PostsQuery = Vec<PostsQueryItem>;
 
 pub async fn query_posts<C: Connection>(
     db: &'_ Surreal<C>
@@ -910,9 +1991,148 @@ pub async fn query_posts<C: Connection>(
         );
     }
 
+    #[test]
+    fn extract_last_result_of_comment_mutation() {
+        const MUTATION_CONTENT: &str =
+            include_str!("../../templates/projects/blog/mutations/comment.surql");
+
+        let statements = surrealdb::sql::parse(MUTATION_CONTENT).unwrap().0 .0;
+
+        let results = extract_query_results(
+            "CommentMutation",
+            &statements,
+            QueryResultMode::Last,
+            &TableDefinitions::new(),
+        );
+
+        assert_eq!(
+            results,
+            vec![QueryResult {
+                index: 2,
+                response_type: "CommentMutation".to_string(),
+                structs: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn extract_all_results_of_transaction() {
+        let statements = surrealdb::sql::parse(
+            "BEGIN TRANSACTION;
+LET $user = (SELECT * FROM $auth);
+CREATE post SET title = $title, author = $user.id;
+UPDATE $user SET number_of_posts += 1;
+COMMIT TRANSACTION;",
+        )
+        .unwrap()
+        .0
+         .0;
+
+        let results = extract_query_results(
+            "CreatePostMutation",
+            &statements,
+            QueryResultMode::All,
+            &TableDefinitions::new(),
+        );
+
+        assert_eq!(
+            results,
+            vec![
+                QueryResult {
+                    index: 1,
+                    response_type: "CreatePostMutationResult0".to_string(),
+                    structs: None,
+                },
+                QueryResult {
+                    index: 2,
+                    response_type: "CreatePostMutationResult1".to_string(),
+                    structs: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_mutation_content_with_tuple_result() {
+        let file_name = "create_post";
+        let variables = vec![QueryVariable {
+            name: "title".to_string(),
+            type_: "String".to_string(),
+        }];
+        let response_type = "CreatePostMutation";
+        let results = vec![
+            QueryResult {
+                index: 1,
+                response_type: "CreatePostMutationResult0".to_string(),
+                structs: Some(vec![NestedStruct {
+                    name: "CreatePostMutationResult0Item".to_string(),
+                    fields: vec![StructField {
+                        name: "title".to_string(),
+                        type_str: "String".to_string(),
+                        is_optional: false,
+                    }],
+                }]),
+            },
+            QueryResult {
+                index: 2,
+                response_type: "CreatePostMutationResult1".to_string(),
+                structs: None,
+            },
+        ];
+
+        let result = generate_from_mutation_template(
+            "mutations".to_string(),
+            file_name.to_string(),
+            variables,
+            response_type.to_string(),
+            results,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            "use serde::{Deserialize, Serialize};
+use surrealdb::{Surreal, Connection, Result};
+
+pub use crate::models::mutations::CreatePostMutationResult1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreatePostMutationResult0Item {
+    pub title: String,
+}
+
+pub type CreatePostMutationResult0 = Vec<CreatePostMutationResult0Item>;
+
+pub type CreatePostMutation = (
+    CreatePostMutationResult0,
+    CreatePostMutationResult1,
+);
+
+pub async fn mutate_create_post<C: Connection>(
+    db: &'_ Surreal<C>,
+    title: String
+) -> Result<CreatePostMutation> {
+    const QUERY: &str = include_str!(\"../../../mutations/create_post.surql\");
+
+    let mut response = db
+        .query(QUERY)
+        .bind((\"title\", title))
+        .await?;
+
+    let result: CreatePostMutation = (
+        response.take(1)?,
+        response.take(2)?,
+    );
+
+    Ok(result)
+}"
+        );
+    }
+
     #[test]
     fn extract_empty_struct_fields() {
-        let struct_fields = extract_struct_fields(vec![], false);
+        let (struct_fields, _) = extract_struct_fields("Empty", vec![], &[], false);
         let result = vec![];
 
         assert_eq!(struct_fields, result);
@@ -920,10 +2140,11 @@ pub async fn query_posts<C: Connection>(
 
     #[test]
     fn extract_empty_struct_fields_with_id() {
-        let struct_fields = extract_struct_fields(vec![], true);
+        let (struct_fields, _) = extract_struct_fields("Empty", vec![], &[], true);
         let result = vec![StructField {
             name: "id".to_string(),
             type_str: "Thing".to_string(),
+            is_optional: false,
         }];
 
         assert_eq!(struct_fields, result);
@@ -949,23 +2170,27 @@ pub async fn query_posts<C: Connection>(
             },
         ];
 
-        let struct_fields = extract_struct_fields(define_field_statements, true);
+        let (struct_fields, _) = extract_struct_fields("Post", define_field_statements, &[], true);
         let result = vec![
             StructField {
                 name: "content".to_string(),
-                type_str: "String".to_string(),
+                type_str: "Option<String>".to_string(),
+                is_optional: true,
             },
             StructField {
                 name: "id".to_string(),
                 type_str: "Thing".to_string(),
+                is_optional: false,
             },
             StructField {
                 name: "name".to_string(),
-                type_str: "String".to_string(),
+                type_str: "Option<String>".to_string(),
+                is_optional: true,
             },
             StructField {
                 name: "title".to_string(),
-                type_str: "String".to_string(),
+                type_str: "Option<String>".to_string(),
+                is_optional: true,
             },
         ];
 
@@ -986,38 +2211,450 @@ DEFINE FIELD permissions.* ON user TYPE record (permission);").unwrap();
 
         let define_field_statements = extract_define_field_statements(statements);
 
-        let struct_fields = extract_struct_fields(define_field_statements, true);
+        let (struct_fields, _) = extract_struct_fields("User", define_field_statements, &[], true);
         let result = vec![
             StructField {
                 name: "avatar".to_string(),
-                type_str: "String".to_string(),
+                type_str: "Option<String>".to_string(),
+                is_optional: true,
             },
             StructField {
                 name: "email".to_string(),
                 type_str: "String".to_string(),
+                is_optional: false,
             },
             StructField {
                 name: "id".to_string(),
                 type_str: "Thing".to_string(),
+                is_optional: false,
             },
             StructField {
                 name: "password".to_string(),
                 type_str: "String".to_string(),
+                is_optional: false,
             },
             StructField {
                 name: "permissions".to_string(),
-                type_str: "Vec<Thing>".to_string(),
+                type_str: "Option<Vec<Thing>>".to_string(),
+                is_optional: true,
             },
             StructField {
                 name: "registered_at".to_string(),
-                type_str: "String".to_string(),
+                type_str: "Option<chrono::DateTime<chrono::Utc>>".to_string(),
+                is_optional: true,
             },
             StructField {
                 name: "username".to_string(),
                 type_str: "String".to_string(),
+                is_optional: false,
             },
         ];
 
         assert_eq!(struct_fields, result);
     }
+
+    #[test]
+    fn extract_struct_fields_with_all_types() {
+        let query = surrealdb::sql::parse(
+            "DEFINE FIELD is_active ON metric TYPE bool;
+DEFINE FIELD raw ON metric TYPE bytes;
+DEFINE FIELD measured_at ON metric TYPE datetime;
+DEFINE FIELD price ON metric TYPE decimal;
+DEFINE FIELD period ON metric TYPE duration;
+DEFINE FIELD ratio ON metric TYPE float;
+DEFINE FIELD count ON metric TYPE int;
+DEFINE FIELD value ON metric TYPE number;
+DEFINE FIELD metadata ON metric TYPE object;
+DEFINE FIELD location ON metric TYPE geometry (point);
+DEFINE FIELD extra ON metric TYPE any;
+DEFINE FIELD untyped ON metric;
+DEFINE FIELD tags ON metric TYPE array;",
+        )
+        .unwrap();
+        let statements = query.0 .0;
+
+        let define_field_statements = extract_define_field_statements(statements);
+
+        let (struct_fields, _) =
+            extract_struct_fields("Metric", define_field_statements, &[], false);
+        let result = to_struct_fields(vec![
+            ("count", "Option<i64>"),
+            ("extra", "Option<serde_json::Value>"),
+            ("is_active", "Option<bool>"),
+            ("location", "Option<surrealdb::sql::Geometry>"),
+            ("measured_at", "Option<chrono::DateTime<chrono::Utc>>"),
+            ("metadata", "Option<serde_json::Value>"),
+            ("period", "Option<surrealdb::sql::Duration>"),
+            ("price", "Option<rust_decimal::Decimal>"),
+            ("ratio", "Option<f64>"),
+            ("raw", "Option<Vec<u8>>"),
+            ("tags", "Option<Vec<serde_json::Value>>"),
+            ("untyped", "Option<serde_json::Value>"),
+            ("value", "Option<f64>"),
+        ]);
+
+        assert_eq!(struct_fields, result);
+    }
+
+    fn extract_blog_table_struct_fields(content: &str, table_name: &str) -> Vec<StructField> {
+        let query = surrealdb::sql::parse(content).unwrap();
+        let statements = query.0 .0;
+
+        let define_field_statements = extract_define_field_statements(statements)
+            .into_iter()
+            .filter(|define_field_statement| define_field_statement.what.to_string() == table_name)
+            .collect::<Vec<_>>();
+
+        let struct_name = table_name.to_case(Case::Pascal);
+        let (struct_fields, _) =
+            extract_struct_fields(&struct_name, define_field_statements, &[], true);
+
+        struct_fields
+    }
+
+    fn to_struct_fields(fields: Vec<(&str, &str)>) -> Vec<StructField> {
+        fields
+            .into_iter()
+            .map(|(name, type_str)| StructField {
+                name: name.to_string(),
+                type_str: type_str.to_string(),
+                is_optional: type_str.starts_with("Option<"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extract_blog_comment_struct_fields() {
+        const SCHEMA_CONTENT: &str =
+            include_str!("../../templates/projects/blog/schemas/comment.surql");
+
+        let struct_fields = extract_blog_table_struct_fields(SCHEMA_CONTENT, "comment");
+
+        assert_eq!(
+            struct_fields,
+            to_struct_fields(vec![
+                ("content", "String"),
+                ("created_at", "Option<chrono::DateTime<chrono::Utc>>"),
+                ("id", "Thing"),
+            ])
+        );
+    }
+
+    #[test]
+    fn extract_blog_permission_struct_fields() {
+        const SCHEMA_CONTENT: &str =
+            include_str!("../../templates/projects/blog/schemas/permission.surql");
+
+        let struct_fields = extract_blog_table_struct_fields(SCHEMA_CONTENT, "permission");
+
+        assert_eq!(
+            struct_fields,
+            to_struct_fields(vec![
+                ("created_at", "Option<chrono::DateTime<chrono::Utc>>"),
+                ("id", "Thing"),
+                ("name", "Option<String>"),
+            ])
+        );
+    }
+
+    #[test]
+    fn extract_blog_post_struct_fields() {
+        const SCHEMA_CONTENT: &str =
+            include_str!("../../templates/projects/blog/schemas/post.surql");
+
+        let struct_fields = extract_blog_table_struct_fields(SCHEMA_CONTENT, "post");
+
+        assert_eq!(
+            struct_fields,
+            to_struct_fields(vec![
+                ("author", "Thing"),
+                ("content", "Option<String>"),
+                ("created_at", "Option<chrono::DateTime<chrono::Utc>>"),
+                ("id", "Thing"),
+                ("status", "Option<String>"),
+                ("title", "Option<String>"),
+            ])
+        );
+    }
+
+    #[test]
+    fn extract_blog_script_migration_struct_fields() {
+        const SCHEMA_CONTENT: &str =
+            include_str!("../../templates/projects/blog/schemas/script_migration.surql");
+
+        let struct_fields = extract_blog_table_struct_fields(SCHEMA_CONTENT, "script_migration");
+
+        assert_eq!(
+            struct_fields,
+            to_struct_fields(vec![
+                ("executed_at", "Option<chrono::DateTime<chrono::Utc>>"),
+                ("id", "Thing"),
+                ("script_name", "Option<String>"),
+            ])
+        );
+    }
+
+    #[test]
+    fn extract_blog_user_struct_fields() {
+        const SCHEMA_CONTENT: &str =
+            include_str!("../../templates/projects/blog/schemas/user.surql");
+
+        let struct_fields = extract_blog_table_struct_fields(SCHEMA_CONTENT, "user");
+
+        assert_eq!(
+            struct_fields,
+            to_struct_fields(vec![
+                ("avatar", "Option<String>"),
+                ("email", "String"),
+                ("id", "Thing"),
+                ("password", "String"),
+                ("permissions", "Option<Vec<Thing>>"),
+                ("registered_at", "Option<chrono::DateTime<chrono::Utc>>"),
+                ("username", "String"),
+            ])
+        );
+    }
+
+    #[test]
+    fn extract_optional_struct_fields() {
+        let (content, optional_fields) = strip_option_kinds(
+            "DEFINE FIELD title ON post TYPE string ASSERT $value != NONE;
+DEFINE FIELD subtitle ON post TYPE option<string> ASSERT string::len($value) > 3;
+DEFINE FIELD status ON post TYPE string ASSERT $value == NONE OR $value INSIDE ['DRAFT', 'PUBLISHED'];
+DEFINE FIELD created_at ON post TYPE datetime VALUE $before OR time::now() ASSERT $value != NONE;",
+        )
+        .unwrap();
+        let query = surrealdb::sql::parse(&content).unwrap();
+        let statements = query.0 .0;
+
+        let define_field_statements = extract_define_field_statements(statements);
+
+        let (struct_fields, _) =
+            extract_struct_fields("Post", define_field_statements, &optional_fields, false);
+
+        assert_eq!(
+            struct_fields,
+            to_struct_fields(vec![
+                ("created_at", "Option<chrono::DateTime<chrono::Utc>>"),
+                ("status", "Option<String>"),
+                ("subtitle", "Option<String>"),
+                ("title", "String"),
+            ])
+        );
+    }
+
+    #[test]
+    fn extract_nested_struct_fields() {
+        let query = surrealdb::sql::parse(
+            "DEFINE FIELD username ON user TYPE string ASSERT $value != NONE;
+DEFINE FIELD settings ON user TYPE object ASSERT $value != NONE;
+DEFINE FIELD settings.theme ON user TYPE string ASSERT $value != NONE;
+DEFINE FIELD settings.notifications.email ON user TYPE bool;
+DEFINE FIELD address.city ON user TYPE string;
+DEFINE FIELD contacts ON user TYPE array;
+DEFINE FIELD contacts.*.name ON user TYPE string ASSERT $value != NONE;
+DEFINE FIELD contacts.*.phone ON user TYPE string;",
+        )
+        .unwrap();
+        let statements = query.0 .0;
+
+        let define_field_statements = extract_define_field_statements(statements);
+
+        let (struct_fields, nested_structs) =
+            extract_struct_fields("User", define_field_statements, &[], true);
+
+        assert_eq!(
+            struct_fields,
+            to_struct_fields(vec![
+                ("address", "Option<UserAddress>"),
+                ("contacts", "Option<Vec<UserContacts>>"),
+                ("id", "Thing"),
+                ("settings", "UserSettings"),
+                ("username", "String"),
+            ])
+        );
+        assert_eq!(
+            nested_structs,
+            vec![
+                NestedStruct {
+                    name: "UserAddress".to_string(),
+                    fields: to_struct_fields(vec![("city", "Option<String>")]),
+                },
+                NestedStruct {
+                    name: "UserContacts".to_string(),
+                    fields: to_struct_fields(vec![("name", "String"), ("phone", "Option<String>")]),
+                },
+                NestedStruct {
+                    name: "UserSettingsNotifications".to_string(),
+                    fields: to_struct_fields(vec![("email", "Option<bool>")]),
+                },
+                NestedStruct {
+                    name: "UserSettings".to_string(),
+                    fields: to_struct_fields(vec![
+                        ("notifications", "Option<UserSettingsNotifications>"),
+                        ("theme", "String"),
+                    ]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_crud_content_with_nested_structs() {
+        let table_name = "user";
+        let struct_name = "User";
+        let struct_fields = to_struct_fields(vec![("id", "Thing"), ("settings", "UserSettings")]);
+        let nested_structs = vec![NestedStruct {
+            name: "UserSettings".to_string(),
+            fields: to_struct_fields(vec![("theme", "Option<String>")]),
+        }];
+
+        let result = generate_from_crud_template(
+            table_name.to_string(),
+            struct_name.to_string(),
+            struct_fields,
+            nested_structs,
+        )
+        .unwrap();
+
+        assert!(result.starts_with(
+            "use serde::{Deserialize, Serialize};
+use surrealdb::{sql::Thing, Connection, Result, Surreal};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    pub id: Thing,
+    pub settings: UserSettings,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserSettings {
+    #[serde(skip_serializing_if = \"Option::is_none\")]
+    pub theme: Option<String>,
+}
+
+pub async fn get_all_user"
+        ));
+    }
+
+    #[test]
+    fn get_modules_affected_by_schema_and_query_changes() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let schemas_dir = dir.join("schemas");
+        let queries_dir = dir.join("queries");
+        std::fs::create_dir_all(&schemas_dir).unwrap();
+        std::fs::create_dir_all(&queries_dir).unwrap();
+
+        let watched_dirs = vec![
+            (
+                schemas_dir.canonicalize().unwrap(),
+                vec![DbModule::Crud, DbModule::Mutations, DbModule::Queries],
+            ),
+            (queries_dir.canonicalize().unwrap(), vec![DbModule::Queries]),
+        ];
+
+        let queries_modules =
+            get_affected_modules(&watched_dirs, &[queries_dir.join("posts.surql")]);
+        let all_modules = get_affected_modules(
+            &watched_dirs,
+            &[
+                queries_dir.join("posts.surql"),
+                schemas_dir.join("post.surql"),
+            ],
+        );
+
+        assert_eq!(queries_modules, vec![DbModule::Queries]);
+        assert_eq!(
+            all_modules,
+            vec![DbModule::Crud, DbModule::Mutations, DbModule::Queries]
+        );
+    }
+
+    #[test]
+    fn ignore_changes_of_non_surql_files() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        let watched_dirs = vec![(dir.canonicalize().unwrap(), vec![DbModule::Queries])];
+
+        let modules = get_affected_modules(
+            &watched_dirs,
+            &[dir.join("posts.surql.swp"), dir.join("posts.surql~")],
+        );
+
+        assert_eq!(modules, vec![]);
+    }
+
+    #[test]
+    fn detect_file_changes() {
+        use notify::event::{CreateKind, DataChange, RemoveKind, RenameMode};
+
+        assert!(is_file_change(&EventKind::Create(CreateKind::File)));
+        assert!(is_file_change(&EventKind::Remove(RemoveKind::File)));
+        assert!(is_file_change(&EventKind::Modify(ModifyKind::Data(
+            DataChange::Content
+        ))));
+        assert!(is_file_change(&EventKind::Modify(ModifyKind::Name(
+            RenameMode::Both
+        ))));
+        assert!(is_file_change(&EventKind::Access(AccessKind::Close(
+            AccessMode::Write
+        ))));
+
+        assert!(!is_file_change(&EventKind::Access(AccessKind::Open(
+            AccessMode::Read
+        ))));
+        assert!(!is_file_change(&EventKind::Modify(ModifyKind::Metadata(
+            notify::event::MetadataKind::Any
+        ))));
+    }
+
+    #[test]
+    fn generate_db_mod_file_from_non_empty_modules() {
+        let db_modules = DbModules(BTreeMap::from([
+            (DbModule::Crud, BTreeMap::new()),
+            (
+                DbModule::Queries,
+                BTreeMap::from([
+                    (
+                        PathBuf::from("src/db/queries.rs"),
+                        "pub mod posts;".to_string(),
+                    ),
+                    (PathBuf::from("src/db/queries/posts.rs"), "".to_string()),
+                ]),
+            ),
+        ]));
+
+        let generated_files = db_modules
+            .to_generated_files(&SurrealdbConfig::default())
+            .unwrap();
+
+        assert_eq!(
+            generated_files.get(Path::new("src/db.rs")),
+            Some(&"#[cfg(feature = \"ssr\")]\npub mod connection;\npub mod queries;".to_string())
+        );
+        assert!(generated_files.contains_key(Path::new("src/db/connection.rs")));
+        assert_eq!(generated_files.len(), 4);
+    }
+
+    #[test]
+    fn build_generation_status_from_changes() {
+        let changes = DbChanges {
+            added: vec!["db::queries::posts".to_string()],
+            changed: vec!["db::crud::post".to_string()],
+            removed: vec![],
+        };
+
+        assert!(matches!(
+            GenerationStatus::new(&Ok(changes)),
+            GenerationStatus::Generated { changes: 2, .. }
+        ));
+        assert!(matches!(
+            GenerationStatus::new(&Ok(DbChanges::default())),
+            GenerationStatus::UpToDate { .. }
+        ));
+        assert!(matches!(
+            GenerationStatus::new(&Err(anyhow!("1 file could not be parsed"))),
+            GenerationStatus::Failed { message, .. } if message == "1 file could not be parsed"
+        ));
+    }
 }
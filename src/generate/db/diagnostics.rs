@@ -0,0 +1,218 @@
+use anyhow::{anyhow, Error};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+use surrealdb::sql::Query;
+
+use crate::logger::{self, LogSource};
+
+/// A `.surql` file that cannot be parsed, displayed like a compiler error:
+///
+/// ```text
+/// error: cannot parse queries/posts.surql
+///  --> queries/posts.surql:1:1
+///   |
+/// 1 | SELEC * FRM post;
+///   | ^
+///   = near 'SELEC * FRM post;'
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    file_path: PathBuf,
+    line: Option<usize>,
+    column: Option<usize>,
+    source_line: Option<String>,
+    message: String,
+}
+
+/// Parse errors of every file that failed during a generation, only reported once per file.
+#[derive(Debug, Default)]
+pub struct ParseErrors(BTreeMap<PathBuf, ParseError>);
+
+impl ParseErrors {
+    pub fn push(&mut self, error: ParseError) {
+        self.0.entry(error.file_path.clone()).or_insert(error);
+    }
+
+    pub fn extend(&mut self, errors: ParseErrors) {
+        for error in errors.0.into_values() {
+            self.push(error);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Print every error on stderr and return the summary of the failures.
+    pub fn report(&self) -> Error {
+        for error in self.0.values() {
+            logger::log_error(LogSource::Gen, error);
+        }
+
+        let number_of_files = self.0.len();
+        match number_of_files {
+            1 => anyhow!("1 file could not be parsed"),
+            _ => anyhow!("{} files could not be parsed", number_of_files),
+        }
+    }
+}
+
+/// Parse a `.surql` file. `content` can be a preprocessed version of `original_content` as long as lines are kept.
+pub fn parse_surql_file(
+    file_path: &Path,
+    original_content: &str,
+    content: &str,
+) -> Result<Query, ParseError> {
+    surrealdb::sql::parse(content)
+        .map_err(|error| to_parse_error(file_path, original_content, error))
+}
+
+fn to_parse_error(
+    file_path: &Path,
+    original_content: &str,
+    error: surrealdb::err::Error,
+) -> ParseError {
+    use surrealdb::err::Error::*;
+
+    let (line, column, message) = match error {
+        InvalidQuery { line, char, sql } => {
+            let near = sql.lines().next().unwrap_or_default().trim_end();
+            (Some(line), Some(char), format!("near '{}'", near))
+        }
+        InvalidField { line, .. }
+        | InvalidSplit { line, .. }
+        | InvalidOrder { line, .. }
+        | InvalidGroup { line, .. } => (Some(line), None, error.to_string()),
+        QueryEmpty => (
+            None,
+            None,
+            "the file does not contain any statement".to_string(),
+        ),
+        error => (None, None, error.to_string()),
+    };
+
+    // 💡 surrealdb returns line 0 when the position cannot be located
+    let line = line.filter(|line| *line > 0);
+
+    let source_line = line.and_then(|line| {
+        original_content
+            .lines()
+            .nth(line - 1)
+            .map(|source_line| source_line.trim_end().to_string())
+    });
+
+    // 💡 surrealdb returns the byte offset in the line, displayed as a 1-based column of chars
+    let column = match (column, &source_line) {
+        (Some(column), Some(source_line)) => {
+            let prefix = source_line
+                .char_indices()
+                .take_while(|(index, _)| *index < column)
+                .count();
+            Some(prefix + 1)
+        }
+        _ => None,
+    };
+
+    ParseError {
+        file_path: file_path.to_path_buf(),
+        line,
+        column,
+        source_line,
+        message,
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file_name = self.file_path.to_string_lossy().replace('\\', "/");
+
+        writeln!(f, "error: cannot parse {}", file_name)?;
+
+        let (line, source_line) = match (self.line, &self.source_line) {
+            (Some(line), Some(source_line)) => (line, source_line),
+            _ => return write!(f, "  = {}", self.message),
+        };
+
+        let gutter = " ".repeat(line.to_string().len());
+
+        match self.column {
+            Some(column) => writeln!(f, "{}--> {}:{}:{}", gutter, file_name, line, column)?,
+            None => writeln!(f, "{}--> {}:{}", gutter, file_name, line)?,
+        }
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line, source_line)?;
+
+        if let Some(column) = self.column {
+            writeln!(f, "{} | {}^", gutter, " ".repeat(column - 1))?;
+        }
+
+        write!(f, "{} = {}", gutter, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn display_error_with_caret_at_invalid_statement() {
+        let content = "SELECT * FROM post;\n\nSELEC * FRM comment;";
+
+        let error =
+            parse_surql_file(Path::new("queries/posts.surql"), content, content).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "error: cannot parse queries/posts.surql
+ --> queries/posts.surql:3:1
+  |
+3 | SELEC * FRM comment;
+  | ^
+  = near 'SELEC * FRM comment;'"
+        );
+    }
+
+    #[test]
+    fn display_error_with_caret_in_the_middle_of_a_line() {
+        let content = "SELECT * FROM post WHERE ORDER BY title;";
+
+        let error =
+            parse_surql_file(Path::new("queries/posts.surql"), content, content).unwrap_err();
+
+        assert_eq!(error.line, Some(1));
+        assert_eq!(error.column, Some(32));
+        assert!(error.to_string().contains(
+            "1 | SELECT * FROM post WHERE ORDER BY title;
+  |                                ^
+  = near 'BY title;'"
+        ));
+    }
+
+    #[test]
+    fn display_error_of_empty_file() {
+        let error = parse_surql_file(Path::new("queries/empty.surql"), "", "").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "error: cannot parse queries/empty.surql
+  = the file does not contain any statement"
+        );
+    }
+
+    #[test]
+    fn report_each_file_once() {
+        let mut errors = ParseErrors::default();
+
+        for _ in 0..2 {
+            let error = parse_surql_file(Path::new("schemas/post.surql"), "", "").unwrap_err();
+            errors.push(error);
+        }
+
+        assert_eq!(errors.0.len(), 1);
+        assert_eq!(errors.report().to_string(), "1 file could not be parsed");
+    }
+}
@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+const MANIFEST_FILE_PATH: &str = "target/ultime/db-manifest.toml";
+
+/// List of the files generated by the last run, with the hash of their content.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Manifest {
+    files: BTreeMap<String, String>,
+}
+
+/// Modules added, changed or removed by a generation, e.g. `db::queries::posts`.
+#[derive(Debug, Default, PartialEq)]
+pub struct DbChanges {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl DbChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Write the generated files that changed since the last run and remove the ones that are no longer generated.
+pub fn apply_generated_files(generated_files: &BTreeMap<PathBuf, String>) -> Result<DbChanges> {
+    let manifest_file_path = Path::new(MANIFEST_FILE_PATH);
+    let previous_manifest = read_manifest(manifest_file_path);
+
+    let mut manifest = Manifest::default();
+    let mut changes = DbChanges::default();
+
+    for (file_path, content) in generated_files {
+        let hash = compute_hash(content);
+        manifest
+            .files
+            .insert(to_manifest_key(file_path), hash.to_string());
+
+        let existing_content = match file_path.exists() {
+            true => Some(std::fs::read_to_string(file_path)?),
+            false => None,
+        };
+
+        // 💡 skip unchanged files so that cargo-leptos does not rebuild the app
+        if existing_content.as_deref().map(compute_hash) == Some(hash) {
+            continue;
+        }
+
+        if let Some(parent_dir) = file_path.parent() {
+            fs_extra::dir::create_all(parent_dir, false)?;
+        }
+        std::fs::write(file_path, content)?;
+
+        if let Some(module) = get_module_name(file_path) {
+            match existing_content {
+                Some(_) => changes.changed.push(module),
+                None => changes.added.push(module),
+            }
+        }
+    }
+
+    for file_path in find_orphan_files(generated_files)? {
+        std::fs::remove_file(&file_path)?;
+        remove_empty_parent_dirs(&file_path)?;
+
+        if let Some(module) = get_module_name(&file_path) {
+            changes.removed.push(module);
+        }
+    }
+
+    if manifest != previous_manifest {
+        write_manifest(manifest_file_path, &manifest)?;
+    }
+
+    Ok(changes)
+}
+
+/// Files that are not generated anymore, removed by the generation and reported by `--check`.
+/// The manifest is usually missing on a fresh clone (e.g. in CI), so every file of the `db` folder is an orphan too.
+pub fn find_orphan_files(generated_files: &BTreeMap<PathBuf, String>) -> Result<Vec<PathBuf>> {
+    let manifest = read_manifest(Path::new(MANIFEST_FILE_PATH));
+
+    let mut file_paths = manifest.files.keys().map(PathBuf::from).collect::<Vec<_>>();
+
+    let db_mod_file_path = PathBuf::from("src/db.rs");
+    file_paths.push(db_mod_file_path);
+    list_rust_files(Path::new("src/db"), &mut file_paths)?;
+
+    let orphan_file_paths = file_paths
+        .into_iter()
+        .filter(|file_path| file_path.exists() && !generated_files.contains_key(file_path))
+        .unique()
+        .sorted()
+        .collect();
+
+    Ok(orphan_file_paths)
+}
+
+fn list_rust_files(dir: &Path, file_paths: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    for entry in dir.read_dir()? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            list_rust_files(&path, file_paths)?;
+        } else if path.extension().and_then(|extension| extension.to_str()) == Some("rs") {
+            file_paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// A missing or invalid manifest is considered empty: no file will be removed.
+fn read_manifest(manifest_file_path: &Path) -> Manifest {
+    std::fs::read_to_string(manifest_file_path)
+        .ok()
+        .and_then(|content| toml::from_str::<Manifest>(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_manifest(manifest_file_path: &Path, manifest: &Manifest) -> Result<()> {
+    if let Some(parent_dir) = manifest_file_path.parent() {
+        fs_extra::dir::create_all(parent_dir, false)?;
+    }
+
+    let content = toml::to_string(manifest).context("Cannot serialize db manifest")?;
+    std::fs::write(manifest_file_path, content)?;
+
+    Ok(())
+}
+
+fn compute_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+fn to_manifest_key(file_path: &Path) -> String {
+    file_path.to_string_lossy().replace('\\', "/")
+}
+
+/// Only the modules inside the `db` folder are reported, e.g. `src/db/queries/posts.rs` is `db::queries::posts`.
+/// The `mod` files (`src/db.rs`, `src/db/queries.rs`, ...) are updated along with them.
+fn get_module_name(file_path: &Path) -> Option<String> {
+    let segments = file_path
+        .with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+
+    match segments.as_slice() {
+        [src, db, kind, name] if src == "src" && db == "db" => {
+            Some(format!("{}::{}::{}", db, kind, name))
+        }
+        _ => None,
+    }
+}
+
+fn remove_empty_parent_dirs(file_path: &Path) -> Result<()> {
+    let mut parent_dir = file_path.parent();
+
+    while let Some(dir) = parent_dir {
+        if dir.as_os_str().is_empty() || dir == Path::new("src") {
+            break;
+        }
+
+        let is_empty = dir.read_dir()?.next().is_none();
+        if !is_empty {
+            break;
+        }
+
+        std::fs::remove_dir(dir)?;
+        parent_dir = dir.parent();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn get_module_name_of_generated_query() {
+        let module = get_module_name(Path::new("src/db/queries/posts.rs"));
+
+        assert_eq!(module, Some("db::queries::posts".to_string()));
+    }
+
+    #[test]
+    fn get_no_module_name_of_mod_files() {
+        assert_eq!(get_module_name(Path::new("src/db.rs")), None);
+        assert_eq!(get_module_name(Path::new("src/db/queries.rs")), None);
+    }
+
+    #[test]
+    fn parse_manifest() {
+        let manifest = Manifest {
+            files: BTreeMap::from([
+                ("src/db.rs".to_string(), compute_hash("pub mod crud;")),
+                ("src/db/crud.rs".to_string(), compute_hash("pub mod post;")),
+            ]),
+        };
+
+        let content = toml::to_string(&manifest).unwrap();
+
+        assert_eq!(toml::from_str::<Manifest>(&content).unwrap(), manifest);
+    }
+}
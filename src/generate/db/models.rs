@@ -0,0 +1,674 @@
+use convert_case::{Case, Casing};
+use std::collections::HashMap;
+use surrealdb::sql::{
+    statements::{DefineFieldStatement, SelectStatement},
+    Field, Function, Idiom, Number, Operator, Part, Statement, Subquery, Value,
+};
+
+use crate::generate::common::OptionalField;
+
+use super::{
+    extract_struct_fields, get_output_type, get_surreal_type, is_optional_struct_field,
+    NestedStruct, StructField, SurrealType,
+};
+
+/// Fields of a table, as defined in the `/schemas` and `/events` folders.
+#[derive(Debug, Clone, Default)]
+pub struct TableDefinition {
+    pub define_field_statements: Vec<DefineFieldStatement>,
+    pub optional_fields: Vec<OptionalField>,
+}
+
+pub type TableDefinitions = HashMap<String, TableDefinition>;
+
+/// Infer the structs describing the result of a statement of a query/mutation.
+/// The item struct, named `{response_type}Item`, is always the last one of the list.
+/// Returns `None` as soon as a projection cannot be typed.
+pub fn infer_response_structs(
+    response_type: &str,
+    statement: &Statement,
+    tables: &TableDefinitions,
+) -> Option<Vec<NestedStruct>> {
+    let item_struct_name = format!("{}Item", response_type);
+    let mut structs = vec![];
+
+    let fields = match statement {
+        Statement::Select(select) => infer_select_fields(
+            response_type,
+            &item_struct_name,
+            select,
+            None,
+            tables,
+            &mut structs,
+        )?,
+        Statement::Create(create) if create.output.is_none() => {
+            let table = get_single_table_name(&create.what.0)?;
+            infer_table_fields(&item_struct_name, &table, tables, &mut structs)?
+        }
+        Statement::Update(update) if update.output.is_none() => {
+            let table = get_single_table_name(&update.what.0)?;
+            infer_table_fields(&item_struct_name, &table, tables, &mut structs)?
+        }
+        Statement::Relate(relate) if relate.output.is_none() => {
+            let table = get_table_name(&relate.kind, false)?;
+            infer_table_fields(&item_struct_name, &table, tables, &mut structs)?
+        }
+        _ => return None,
+    };
+
+    structs.push(NestedStruct {
+        name: item_struct_name,
+        fields,
+    });
+
+    Some(structs)
+}
+
+/// Every field of the table, used for `SELECT *` and for records returned by mutations.
+fn infer_table_fields(
+    struct_name: &str,
+    table: &str,
+    tables: &TableDefinitions,
+    structs: &mut Vec<NestedStruct>,
+) -> Option<Vec<StructField>> {
+    let table_definition = tables.get(table)?;
+
+    let (fields, nested_structs) = extract_struct_fields(
+        struct_name,
+        table_definition.define_field_statements.clone(),
+        &table_definition.optional_fields,
+        true,
+    );
+
+    structs.extend(nested_structs);
+
+    Some(fields)
+}
+
+fn infer_select_fields(
+    type_prefix: &str,
+    struct_name: &str,
+    select: &SelectStatement,
+    parent_table: Option<&str>,
+    tables: &TableDefinitions,
+    structs: &mut Vec<NestedStruct>,
+) -> Option<Vec<StructField>> {
+    // 💡 `SELECT VALUE` does not return objects
+    if select.expr.1 {
+        return None;
+    }
+
+    let table = match select.what.0.as_slice() {
+        [what] => get_table_name(what, parent_table.is_some())?,
+        _ => return None,
+    };
+
+    let projections = &select.expr.0;
+
+    if projections.iter().any(|field| matches!(field, Field::All)) {
+        return match projections.len() {
+            1 => infer_table_fields(struct_name, &table, tables, structs),
+            _ => None,
+        };
+    }
+
+    projections
+        .iter()
+        .map(|field| {
+            let (field_name, value) = match field {
+                Field::Alone(value) => match value {
+                    Value::Idiom(idiom) => (get_simple_idiom_name(idiom)?, value),
+                    _ => return None,
+                },
+                Field::Alias(value, alias) => (get_simple_idiom_name(alias)?, value),
+                Field::All => return None,
+            };
+
+            let (type_str, is_optional) = infer_value_type(
+                type_prefix,
+                struct_name,
+                &field_name,
+                value,
+                &table,
+                tables,
+                structs,
+            )?;
+
+            let type_str = match is_optional {
+                true => format!("Option<{}>", type_str),
+                false => type_str,
+            };
+
+            Some(StructField {
+                name: field_name,
+                type_str,
+                is_optional,
+            })
+        })
+        .collect()
+}
+
+/// Returns the Rust type of a projected value and whether it can be NONE.
+fn infer_value_type(
+    type_prefix: &str,
+    struct_name: &str,
+    field_name: &str,
+    value: &Value,
+    table: &str,
+    tables: &TableDefinitions,
+    structs: &mut Vec<NestedStruct>,
+) -> Option<(String, bool)> {
+    let field_type = |surreal_type: SurrealType| Some((get_output_type(&surreal_type), false));
+
+    match value {
+        Value::Idiom(idiom) => {
+            infer_idiom_type(struct_name, field_name, idiom, table, tables, structs)
+        }
+        Value::Function(function) => match function.as_ref() {
+            Function::Normal(name, _) => match name.as_str() {
+                "meta::id" | "meta::tb" | "meta::table" => field_type(SurrealType::String),
+                "count" => field_type(SurrealType::Int),
+                "time::now" => field_type(SurrealType::Datetime),
+                _ => None,
+            },
+            Function::Cast(kind, _) => match kind.as_str() {
+                "bool" => field_type(SurrealType::Bool),
+                "datetime" => field_type(SurrealType::Datetime),
+                "decimal" => field_type(SurrealType::Decimal),
+                "duration" => field_type(SurrealType::Duration),
+                "float" => field_type(SurrealType::Float),
+                "int" => field_type(SurrealType::Int),
+                "number" => field_type(SurrealType::Number),
+                "string" => field_type(SurrealType::String),
+                _ => None,
+            },
+            _ => None,
+        },
+        Value::Expression(expression) => match expression.o {
+            Operator::Add | Operator::Sub | Operator::Mul | Operator::Div | Operator::Pow => {
+                let (left, _) = infer_value_type(
+                    type_prefix,
+                    struct_name,
+                    field_name,
+                    &expression.l,
+                    table,
+                    tables,
+                    structs,
+                )?;
+                let (right, _) = infer_value_type(
+                    type_prefix,
+                    struct_name,
+                    field_name,
+                    &expression.r,
+                    table,
+                    tables,
+                    structs,
+                )?;
+
+                let int_type = get_output_type(&SurrealType::Int);
+                let float_type = get_output_type(&SurrealType::Float);
+                let is_number = |type_str: &str| type_str == int_type || type_str == float_type;
+
+                match (left.as_str(), right.as_str()) {
+                    (left, right) if left == int_type && right == int_type => {
+                        field_type(SurrealType::Int)
+                    }
+                    (left, right) if is_number(left) && is_number(right) => {
+                        field_type(SurrealType::Float)
+                    }
+                    _ => None,
+                }
+            }
+            Operator::Equal
+            | Operator::Exact
+            | Operator::NotEqual
+            | Operator::AllEqual
+            | Operator::AnyEqual
+            | Operator::Like
+            | Operator::NotLike
+            | Operator::AllLike
+            | Operator::AnyLike
+            | Operator::LessThan
+            | Operator::LessThanOrEqual
+            | Operator::MoreThan
+            | Operator::MoreThanOrEqual
+            | Operator::Contain
+            | Operator::NotContain
+            | Operator::ContainAll
+            | Operator::ContainAny
+            | Operator::ContainNone
+            | Operator::Inside
+            | Operator::NotInside
+            | Operator::AllInside
+            | Operator::AnyInside
+            | Operator::NoneInside
+            | Operator::Outside
+            | Operator::Intersects => field_type(SurrealType::Bool),
+            // 💡 `OR`, `AND`, `??` and `?:` return one of their operands
+            _ => None,
+        },
+        Value::Subquery(subquery) => match subquery.as_ref() {
+            Subquery::Select(select) => {
+                let nested_struct_name =
+                    format!("{}_{}", type_prefix, field_name).to_case(Case::Pascal);
+
+                let fields = infer_select_fields(
+                    &nested_struct_name,
+                    &nested_struct_name,
+                    select,
+                    Some(table),
+                    tables,
+                    structs,
+                )?;
+
+                structs.push(NestedStruct {
+                    name: nested_struct_name.to_string(),
+                    fields,
+                });
+
+                Some((format!("Vec<{}>", nested_struct_name), false))
+            }
+            Subquery::Value(value) => infer_value_type(
+                type_prefix,
+                struct_name,
+                field_name,
+                value,
+                table,
+                tables,
+                structs,
+            ),
+            _ => None,
+        },
+        Value::True | Value::False => field_type(SurrealType::Bool),
+        Value::Strand(_) => field_type(SurrealType::String),
+        Value::Datetime(_) => field_type(SurrealType::Datetime),
+        Value::Duration(_) => field_type(SurrealType::Duration),
+        Value::Number(Number::Int(_)) => field_type(SurrealType::Int),
+        Value::Number(Number::Float(_)) => field_type(SurrealType::Float),
+        Value::Number(Number::Decimal(_)) => field_type(SurrealType::Decimal),
+        _ => None,
+    }
+}
+
+/// Resolve the type of a field path, following record links when needed.
+/// e.g. `author.username` is the `username` field of the `user` table when `author` is a `record(user)`.
+fn infer_idiom_type(
+    struct_name: &str,
+    field_name: &str,
+    idiom: &Idiom,
+    table: &str,
+    tables: &TableDefinitions,
+    structs: &mut Vec<NestedStruct>,
+) -> Option<(String, bool)> {
+    let parts = &idiom.0;
+
+    // 💡 graph traversal like `->likes->post` returns a list of record ids
+    if !parts.is_empty() && parts.iter().all(|part| matches!(part, Part::Graph(_))) {
+        let type_str = get_output_type(&SurrealType::Array(Box::new(SurrealType::Id)));
+        return Some((type_str, false));
+    }
+
+    let path = parts
+        .iter()
+        .map(|part| match part {
+            Part::Field(ident) => Some(ident.0.to_string()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut current_table = table.to_string();
+    let mut is_optional = false;
+
+    for index in 0..path.len() {
+        let table_definition = tables.get(&current_table)?;
+        let field_path = path[index..].join(".");
+
+        if field_path == "id" {
+            return Some((get_output_type(&SurrealType::Id), is_optional));
+        }
+
+        let find_statement = |name: &str| {
+            table_definition
+                .define_field_statements
+                .iter()
+                .find(|statement| statement.name.to_string() == name)
+        };
+
+        if let Some(statement) = find_statement(&field_path) {
+            let mut nested_structs = vec![];
+
+            let field_type = get_surreal_type(
+                struct_name,
+                field_name,
+                &field_path,
+                statement.kind.as_ref(),
+                &table_definition.define_field_statements,
+                &table_definition.optional_fields,
+                &mut nested_structs,
+            );
+
+            structs.extend(nested_structs);
+
+            is_optional |= is_optional_struct_field(statement, &table_definition.optional_fields);
+
+            return Some((get_output_type(&field_type), is_optional));
+        }
+
+        // 💡 not the final field, so it should be a link to another table
+        let statement = find_statement(&path[index])?;
+
+        current_table = match &statement.kind {
+            Some(surrealdb::sql::Kind::Record(record_tables)) if record_tables.len() == 1 => {
+                record_tables[0].0.to_string()
+            }
+            _ => return None,
+        };
+
+        is_optional |= is_optional_struct_field(statement, &table_definition.optional_fields);
+    }
+
+    None
+}
+
+fn get_single_table_name(values: &[Value]) -> Option<String> {
+    match values {
+        [value] => get_table_name(value, false),
+        _ => None,
+    }
+}
+
+/// Detect the table queried in the `FROM` clause.
+/// Graph traversals (e.g. `FROM <-comment`) are only allowed inside a subquery.
+fn get_table_name(value: &Value, is_subquery: bool) -> Option<String> {
+    match value {
+        Value::Table(table) => Some(table.0.to_string()),
+        Value::Thing(thing) => Some(thing.tb.to_string()),
+        Value::Function(function) => match function.as_ref() {
+            Function::Normal(name, args) if name == "type::thing" => match args.first()? {
+                Value::Strand(strand) => Some(strand.0.to_string()),
+                _ => None,
+            },
+            _ => None,
+        },
+        Value::Idiom(idiom) if is_subquery => match idiom.0.as_slice() {
+            [.., Part::Graph(graph)]
+                if idiom.0.iter().all(|part| matches!(part, Part::Graph(_))) =>
+            {
+                match graph.what.0.as_slice() {
+                    [table] => Some(table.0.to_string()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn get_simple_idiom_name(idiom: &Idiom) -> Option<String> {
+    match idiom.0.as_slice() {
+        [Part::Field(ident)] => Some(ident.0.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    use crate::generate::common::strip_option_kinds;
+
+    fn extract_table_definitions(schemas: &[&str]) -> TableDefinitions {
+        let mut tables = TableDefinitions::new();
+
+        for schema in schemas {
+            let (schema, optional_fields) = strip_option_kinds(schema).unwrap();
+            let statements = surrealdb::sql::parse(&schema).unwrap().0 .0;
+
+            super::super::add_table_definitions(&mut tables, statements, optional_fields);
+        }
+
+        tables
+    }
+
+    fn extract_blog_table_definitions() -> TableDefinitions {
+        extract_table_definitions(&[
+            include_str!("../../../templates/projects/blog/schemas/comment.surql"),
+            include_str!("../../../templates/projects/blog/schemas/permission.surql"),
+            include_str!("../../../templates/projects/blog/schemas/post.surql"),
+            include_str!("../../../templates/projects/blog/schemas/script_migration.surql"),
+            include_str!("../../../templates/projects/blog/schemas/user.surql"),
+        ])
+    }
+
+    fn infer(
+        response_type: &str,
+        query: &str,
+        tables: &TableDefinitions,
+    ) -> Option<Vec<NestedStruct>> {
+        let statements = surrealdb::sql::parse(query).unwrap().0 .0;
+        infer_response_structs(response_type, statements.last().unwrap(), tables)
+    }
+
+    fn to_struct_fields(fields: Vec<(&str, &str)>) -> Vec<StructField> {
+        fields
+            .into_iter()
+            .map(|(name, type_str)| StructField {
+                name: name.to_string(),
+                type_str: type_str.to_string(),
+                is_optional: type_str.starts_with("Option<"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn infer_blog_posts_query() {
+        const QUERY_CONTENT: &str =
+            include_str!("../../../templates/projects/blog/queries/posts.surql");
+
+        let structs = infer(
+            "PostsQuery",
+            QUERY_CONTENT,
+            &extract_blog_table_definitions(),
+        );
+
+        assert_eq!(
+            structs,
+            Some(vec![NestedStruct {
+                name: "PostsQueryItem".to_string(),
+                fields: to_struct_fields(vec![
+                    ("id", "String"),
+                    ("title", "Option<String>"),
+                    ("content", "Option<String>"),
+                    ("status", "Option<String>"),
+                    ("number_of_comments", "i64"),
+                ]),
+            }])
+        );
+    }
+
+    #[test]
+    fn cannot_infer_blog_post_by_id_query() {
+        const QUERY_CONTENT: &str =
+            include_str!("../../../templates/projects/blog/queries/post_by_id.surql");
+
+        let structs = infer(
+            "PostByIdQuery",
+            QUERY_CONTENT,
+            &extract_blog_table_definitions(),
+        );
+
+        assert_eq!(structs, None);
+    }
+
+    #[test]
+    fn cannot_infer_blog_navbar_query() {
+        const QUERY_CONTENT: &str =
+            include_str!("../../../templates/projects/blog/queries/navbar.surql");
+
+        let structs = infer(
+            "NavbarQuery",
+            QUERY_CONTENT,
+            &extract_blog_table_definitions(),
+        );
+
+        assert_eq!(structs, None);
+    }
+
+    #[test]
+    fn infer_select_all_query() {
+        let structs = infer(
+            "UsersQuery",
+            "SELECT * FROM user;",
+            &extract_blog_table_definitions(),
+        );
+
+        assert_eq!(
+            structs,
+            Some(vec![NestedStruct {
+                name: "UsersQueryItem".to_string(),
+                fields: to_struct_fields(vec![
+                    ("avatar", "Option<String>"),
+                    ("email", "String"),
+                    ("id", "Thing"),
+                    ("password", "String"),
+                    ("permissions", "Option<Vec<Thing>>"),
+                    ("registered_at", "Option<chrono::DateTime<chrono::Utc>>"),
+                    ("username", "String"),
+                ]),
+            }])
+        );
+    }
+
+    #[test]
+    fn infer_query_with_record_link_and_subquery() {
+        let tables = extract_table_definitions(&[
+            "DEFINE TABLE user SCHEMAFULL;
+DEFINE FIELD username ON user TYPE string ASSERT $value != NONE;",
+            "DEFINE TABLE post SCHEMAFULL;
+DEFINE FIELD title ON post TYPE string ASSERT $value != NONE;
+DEFINE FIELD author ON post TYPE record (user) ASSERT $value != NONE;
+DEFINE FIELD reviewer ON post TYPE option<record (user)>;",
+            "DEFINE TABLE comment SCHEMAFULL;
+DEFINE FIELD content ON comment TYPE string ASSERT $value != NONE;",
+        ]);
+
+        let structs = infer(
+            "PostDetailsQuery",
+            "SELECT
+    id,
+    title,
+    author.username AS author,
+    reviewer.username AS reviewer,
+    title = 'Hello' AS is_hello,
+    (SELECT content FROM <-comment) AS comments,
+    ->likes AS likes
+FROM post:first;",
+            &tables,
+        );
+
+        assert_eq!(
+            structs,
+            Some(vec![
+                NestedStruct {
+                    name: "PostDetailsQueryComments".to_string(),
+                    fields: to_struct_fields(vec![("content", "String")]),
+                },
+                NestedStruct {
+                    name: "PostDetailsQueryItem".to_string(),
+                    fields: to_struct_fields(vec![
+                        ("id", "Thing"),
+                        ("title", "String"),
+                        ("author", "String"),
+                        ("reviewer", "Option<String>"),
+                        ("is_hello", "bool"),
+                        ("comments", "Vec<PostDetailsQueryComments>"),
+                        ("likes", "Vec<Thing>"),
+                    ]),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn infer_create_mutation() {
+        let structs = infer(
+            "CreatePostMutation",
+            "CREATE post SET title = $title;",
+            &extract_blog_table_definitions(),
+        );
+
+        assert_eq!(
+            structs,
+            Some(vec![NestedStruct {
+                name: "CreatePostMutationItem".to_string(),
+                fields: to_struct_fields(vec![
+                    ("author", "Thing"),
+                    ("content", "Option<String>"),
+                    ("created_at", "Option<chrono::DateTime<chrono::Utc>>"),
+                    ("id", "Thing"),
+                    ("status", "Option<String>"),
+                    ("title", "Option<String>"),
+                ]),
+            }])
+        );
+    }
+
+    #[test]
+    fn cannot_infer_query_on_unknown_table() {
+        let structs = infer(
+            "ProductsQuery",
+            "SELECT name FROM product;",
+            &extract_blog_table_definitions(),
+        );
+
+        assert_eq!(structs, None);
+    }
+
+    #[test]
+    fn cannot_infer_query_with_unknown_function() {
+        let structs = infer(
+            "PostsQuery",
+            "SELECT string::uppercase(title) AS title FROM post;",
+            &extract_blog_table_definitions(),
+        );
+
+        assert_eq!(structs, None);
+    }
+
+    #[test]
+    fn infer_blog_comment_mutation() {
+        const MUTATION_CONTENT: &str =
+            include_str!("../../../templates/projects/blog/mutations/comment.surql");
+
+        let structs = infer(
+            "CommentMutation",
+            MUTATION_CONTENT,
+            &extract_blog_table_definitions(),
+        );
+
+        assert_eq!(
+            structs,
+            Some(vec![NestedStruct {
+                name: "CommentMutationItem".to_string(),
+                fields: to_struct_fields(vec![
+                    ("content", "String"),
+                    ("created_at", "Option<chrono::DateTime<chrono::Utc>>"),
+                    ("id", "Thing"),
+                ]),
+            }])
+        );
+    }
+
+    #[test]
+    fn cannot_infer_let_statement() {
+        let structs = infer(
+            "UserMutation",
+            "LET $user = (SELECT * FROM user);",
+            &extract_blog_table_definitions(),
+        );
+
+        assert_eq!(structs, None);
+    }
+}
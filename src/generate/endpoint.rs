@@ -3,11 +3,17 @@ use convert_case::{Case, Casing};
 use include_dir::{include_dir, Dir};
 use minijinja::{context, Environment};
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
-use crate::cli::GenerateEndpointFromSchemaMethod;
+use crate::{
+    cli::{GenerateEndpointAuth, GenerateEndpointFromSchemaMethod},
+    config::{self, FoldersConfig},
+};
 
-use super::common::{extract_query_variables, QueryVariable};
+use super::common::{
+    ensures_folder_exists, extract_query_variables, register_generated_module, ModuleDeclaration,
+    QueryVariable,
+};
 
 pub struct GenerateEndpointArgs {
     pub name: String,
@@ -16,6 +22,7 @@ pub struct GenerateEndpointArgs {
     pub from_event: Option<String>,
     pub from_schema: Option<String>,
     pub method: Option<GenerateEndpointFromSchemaMethod>,
+    pub auth: GenerateEndpointAuth,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,12 +34,20 @@ struct DetailsForEndpoint {
     params: Vec<QueryVariable>,
 }
 
+/// Source of the endpoint, at most one of them is set.
+struct EndpointDetails {
+    query: Option<DetailsForEndpoint>,
+    mutation: Option<DetailsForEndpoint>,
+    event: Option<DetailsForEndpoint>,
+    schema: Option<DetailsForEndpoint>,
+}
+
 enum EndpointTypeGenerated {
     Empty,
-    Query(DetailsForEndpoint),
-    Mutation(DetailsForEndpoint),
-    Event(DetailsForEndpoint),
-    Schema(DetailsForEndpoint),
+    Query,
+    Mutation,
+    Event,
+    Schema,
 }
 
 const DEFAULT_METHOD: GenerateEndpointFromSchemaMethod = GenerateEndpointFromSchemaMethod::List;
@@ -45,26 +60,19 @@ pub fn main(args: GenerateEndpointArgs) -> Result<()> {
         from_event,
         from_schema,
         method,
+        auth,
     } = args;
 
     let method = method.unwrap_or(DEFAULT_METHOD);
 
-    let query = get_query_details_for_endpoint(from_query)?;
-    let mutation = get_mutation_details_for_endpoint(from_mutation)?;
-    let event = get_event_details_for_endpoint(from_event)?;
-    let schema = get_schema_details_for_endpoint(from_schema, method.clone())?;
-
-    let endpoint_type_generated = match (
-        query.clone(),
-        mutation.clone(),
-        event.clone(),
-        schema.clone(),
-    ) {
-        (None, None, None, None) => EndpointTypeGenerated::Empty,
-        (Some(query), _, _, _) => EndpointTypeGenerated::Query(query),
-        (_, Some(mutation), _, _) => EndpointTypeGenerated::Mutation(mutation),
-        (_, _, Some(event), _) => EndpointTypeGenerated::Event(event),
-        (_, _, _, Some(schema)) => EndpointTypeGenerated::Schema(schema),
+    let config = config::load_config()?;
+    let folders = &config.folders;
+
+    let details = EndpointDetails {
+        query: get_query_details_for_endpoint(folders, from_query)?,
+        mutation: get_mutation_details_for_endpoint(folders, from_mutation)?,
+        event: get_event_details_for_endpoint(folders, from_event)?,
+        schema: get_schema_details_for_endpoint(folders, from_schema, method.clone())?,
     };
 
     let src_dir = Path::new("src");
@@ -72,6 +80,69 @@ pub fn main(args: GenerateEndpointArgs) -> Result<()> {
     let api_dir = src_dir.join("api");
     ensures_folder_exists(&api_dir)?;
 
+    let content = render_endpoint(&name, details, method, auth)?;
+
+    let module_name = name.to_case(Case::Snake);
+    let file_name = format!("{}.rs", module_name);
+
+    let endpoint_file = api_dir.join(file_name);
+
+    std::fs::write(endpoint_file, content)?;
+    register_generated_module(
+        &api_dir,
+        &module_name,
+        ModuleDeclaration::ReExported,
+        ModuleDeclaration::Public,
+    )?;
+
+    println!("Endpoint {} successfully created", name);
+
+    Ok(())
+}
+
+/// Content of the endpoint calling the function of the `db::crud` module of a table for the given method.
+pub fn generate_schema_endpoint_content(
+    folders: &FoldersConfig,
+    name: &str,
+    table_name: &str,
+    method: GenerateEndpointFromSchemaMethod,
+    auth: GenerateEndpointAuth,
+) -> Result<String> {
+    let details = EndpointDetails {
+        query: None,
+        mutation: None,
+        event: None,
+        schema: get_schema_details_for_endpoint(
+            folders,
+            Some(table_name.to_string()),
+            method.clone(),
+        )?,
+    };
+
+    render_endpoint(name, details, method, auth)
+}
+
+fn render_endpoint(
+    name: &str,
+    details: EndpointDetails,
+    method: GenerateEndpointFromSchemaMethod,
+    auth: GenerateEndpointAuth,
+) -> Result<String> {
+    let EndpointDetails {
+        query,
+        mutation,
+        event,
+        schema,
+    } = details;
+
+    let endpoint_type_generated = match (&query, &mutation, &event, &schema) {
+        (None, None, None, None) => EndpointTypeGenerated::Empty,
+        (Some(_), _, _, _) => EndpointTypeGenerated::Query,
+        (_, Some(_), _, _) => EndpointTypeGenerated::Mutation,
+        (_, _, Some(_), _) => EndpointTypeGenerated::Event,
+        (_, _, _, Some(_)) => EndpointTypeGenerated::Schema,
+    };
+
     let template_name = get_template_name(endpoint_type_generated);
 
     let template_content = get_template(template_name)?;
@@ -94,35 +165,19 @@ pub fn main(args: GenerateEndpointArgs) -> Result<()> {
 
     let content = env.render_str(
         &template_content,
-        context! { endpoint_name, function_name, query, mutation, event, schema, method },
+        context! { endpoint_name, function_name, query, mutation, event, schema, method, auth },
     )?;
 
-    let file_name = name.to_case(Case::Snake);
-    let file_name = format!("{}.rs", file_name);
-
-    let endpoint_file = api_dir.join(file_name);
-
-    std::fs::write(endpoint_file, content)?;
-
-    println!("Endpoint {} successfully created", name);
-
-    Ok(())
-}
-
-fn ensures_folder_exists(dir_path: &PathBuf) -> Result<()> {
-    if !dir_path.exists() {
-        fs_extra::dir::create_all(dir_path, false)?;
-    }
-
-    Ok(())
+    Ok(content)
 }
 
 fn get_query_details_for_endpoint(
+    folders: &FoldersConfig,
     from_query: Option<String>,
 ) -> Result<Option<DetailsForEndpoint>> {
     let result = match from_query {
         Some(from_query) => {
-            let queries_dir = Path::new("queries");
+            let queries_dir = Path::new(&folders.queries);
 
             let query_name = get_query_name(from_query);
 
@@ -159,11 +214,12 @@ fn get_query_details_for_endpoint(
 }
 
 fn get_mutation_details_for_endpoint(
+    folders: &FoldersConfig,
     from_mutation: Option<String>,
 ) -> Result<Option<DetailsForEndpoint>> {
     let result = match from_mutation {
         Some(from_query) => {
-            let mutations_dir = Path::new("mutations");
+            let mutations_dir = Path::new(&folders.mutations);
 
             let mutation_name = get_query_name(from_query);
 
@@ -200,11 +256,12 @@ fn get_mutation_details_for_endpoint(
 }
 
 fn get_event_details_for_endpoint(
+    folders: &FoldersConfig,
     from_event: Option<String>,
 ) -> Result<Option<DetailsForEndpoint>> {
     let result = match from_event {
         Some(from_event) => {
-            let events_dir = Path::new("events");
+            let events_dir = Path::new(&folders.events);
 
             let event_name = get_query_name(from_event);
 
@@ -243,12 +300,13 @@ fn get_event_details_for_endpoint(
 }
 
 fn get_schema_details_for_endpoint(
+    folders: &FoldersConfig,
     from_schema: Option<String>,
     method: GenerateEndpointFromSchemaMethod,
 ) -> Result<Option<DetailsForEndpoint>> {
     let result = match from_schema {
         Some(from_schema) => {
-            let schemas_dir = Path::new("schemas");
+            let schemas_dir = Path::new(&folders.schemas);
 
             let schema_name = get_query_name(from_schema);
 
@@ -262,57 +320,45 @@ fn get_schema_details_for_endpoint(
                 )));
             }
 
-            get_method_prefix(method.clone());
-
             let schema_type = schema_name.to_case(Case::Pascal);
 
             let output_type = match method {
-                GenerateEndpointFromSchemaMethod::List => {
+                GenerateEndpointFromSchemaMethod::List
+                | GenerateEndpointFromSchemaMethod::DeleteAll => {
                     format!("Vec<{}>", schema_type)
                 }
-                GenerateEndpointFromSchemaMethod::Find => {
+                GenerateEndpointFromSchemaMethod::Find
+                | GenerateEndpointFromSchemaMethod::Update
+                | GenerateEndpointFromSchemaMethod::Delete => {
                     format!("Option<{}>", schema_type)
                 }
-                GenerateEndpointFromSchemaMethod::DeleteAll => {
-                    format!("Vec<{}>", schema_type)
-                }
-                _ => schema_type.to_string(),
+                GenerateEndpointFromSchemaMethod::Get
+                | GenerateEndpointFromSchemaMethod::Create => schema_type.to_string(),
             };
 
             let short_name = schema_name.to_case(Case::Snake);
 
-            let inner_function_name = match method {
-                GenerateEndpointFromSchemaMethod::List => format!("get_all_{}", short_name),
-                GenerateEndpointFromSchemaMethod::Get => format!("get_{}", short_name),
-                GenerateEndpointFromSchemaMethod::Find => format!("find_{}", short_name),
-                GenerateEndpointFromSchemaMethod::Create => format!("create_{}", short_name),
-                GenerateEndpointFromSchemaMethod::Update => format!("update_{}", short_name),
-                GenerateEndpointFromSchemaMethod::Delete => format!("delete_{}", short_name),
-                GenerateEndpointFromSchemaMethod::DeleteAll => format!("delete_all_{}", short_name),
+            let inner_function_name =
+                format!("{}_{}", get_method_prefix(method.clone()), short_name);
+
+            // 💡 ids are received as `String` since the arguments of a server function are deserialized from the request
+            let id_param = QueryVariable {
+                name: "id".to_string(),
+                type_: "String".to_string(),
+            };
+            let data_param = QueryVariable {
+                name: "data".to_string(),
+                type_: schema_type,
             };
 
             let params = match method {
-                GenerateEndpointFromSchemaMethod::Get => vec![QueryVariable {
-                    name: "id".to_string(),
-                    type_: "&'static str".to_string(),
-                }],
-                GenerateEndpointFromSchemaMethod::Find => vec![QueryVariable {
-                    name: "id".to_string(),
-                    type_: "&'static str".to_string(),
-                }],
-                GenerateEndpointFromSchemaMethod::Create => vec![QueryVariable {
-                    name: "data".to_string(),
-                    type_: schema_type,
-                }],
-                GenerateEndpointFromSchemaMethod::Update => vec![QueryVariable {
-                    name: "data".to_string(),
-                    type_: schema_type,
-                }],
-                GenerateEndpointFromSchemaMethod::Delete => vec![QueryVariable {
-                    name: "data".to_string(),
-                    type_: schema_type,
-                }],
-                _ => vec![],
+                GenerateEndpointFromSchemaMethod::Get
+                | GenerateEndpointFromSchemaMethod::Find
+                | GenerateEndpointFromSchemaMethod::Delete => vec![id_param],
+                GenerateEndpointFromSchemaMethod::Create => vec![data_param],
+                GenerateEndpointFromSchemaMethod::Update => vec![id_param, data_param],
+                GenerateEndpointFromSchemaMethod::List
+                | GenerateEndpointFromSchemaMethod::DeleteAll => vec![],
             };
 
             let details = DetailsForEndpoint {
@@ -346,10 +392,10 @@ fn get_query_name(from: String) -> String {
 fn get_template_name(endpoint_type_generated: EndpointTypeGenerated) -> String {
     let sub_template_name = match endpoint_type_generated {
         EndpointTypeGenerated::Empty => "empty",
-        EndpointTypeGenerated::Query(_) => "query",
-        EndpointTypeGenerated::Mutation(_) => "mutation",
-        EndpointTypeGenerated::Event(_) => "event",
-        EndpointTypeGenerated::Schema(_) => "schema",
+        EndpointTypeGenerated::Query => "query",
+        EndpointTypeGenerated::Mutation => "mutation",
+        EndpointTypeGenerated::Event => "event",
+        EndpointTypeGenerated::Schema => "schema",
     };
 
     format!("endpoint.{}.rs.jinja2", sub_template_name)
@@ -368,9 +414,10 @@ fn get_template(template_name: String) -> Result<String> {
     Ok(template_content)
 }
 
+/// Prefix of the function of the `db::crud` module called by the endpoint, e.g. `get_all_post`.
 fn get_method_prefix(method: GenerateEndpointFromSchemaMethod) -> &'static str {
     match method {
-        GenerateEndpointFromSchemaMethod::List => "list",
+        GenerateEndpointFromSchemaMethod::List => "get_all",
         GenerateEndpointFromSchemaMethod::Get => "get",
         GenerateEndpointFromSchemaMethod::Find => "find",
         GenerateEndpointFromSchemaMethod::Create => "create",
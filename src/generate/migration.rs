@@ -0,0 +1,460 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local};
+use convert_case::{Case, Casing};
+use serde::Deserialize;
+use std::path::Path;
+use surrealdb::sql::{
+    statements::{
+        DefineStatement, RemoveDatabaseStatement, RemoveEventStatement, RemoveFieldStatement,
+        RemoveFunctionStatement, RemoveIndexStatement, RemoveLoginStatement,
+        RemoveNamespaceStatement, RemoveScopeStatement, RemoveStatement, RemoveTableStatement,
+        RemoveTokenStatement,
+    },
+    Query, Statement, Statements,
+};
+
+use crate::config;
+
+use super::common::{ensures_file_does_not_exist, ensures_folder_exists};
+
+/// Folder read by the migration runner, it cannot be changed in `ultime.toml`.
+pub const MIGRATIONS_DIR: &str = "migrations";
+pub const DOWN_MIGRATIONS_DIR: &str = "down";
+/// Definitions of schemas/events saved by the migration runner each time migrations are applied.
+const DEFINITIONS_DIR: &str = "definitions";
+const INITIAL_DEFINITION_NAME: &str = "_initial";
+
+pub struct GenerateMigrationArgs {
+    pub name: String,
+    pub down: bool,
+    pub diff_from_last_migration: bool,
+}
+
+/// Definition of the schemas/events, as stored in `migrations/definitions/_initial.json`.
+#[derive(Debug, Deserialize)]
+struct SchemaMigrationDefinition {
+    schemas: String,
+}
+
+/// Patch of the definition applied by a migration, stored in `migrations/definitions/<migration>.json`.
+#[derive(Debug, Deserialize)]
+struct DefinitionDiff {
+    schemas: Option<String>,
+}
+
+/// Statements of a migration and of its down migration.
+#[derive(Debug, Default, PartialEq)]
+struct MigrationContent {
+    up: String,
+    down: String,
+}
+
+pub fn main(args: GenerateMigrationArgs) -> Result<()> {
+    let GenerateMigrationArgs {
+        name,
+        down,
+        diff_from_last_migration,
+    } = args;
+
+    let content = match diff_from_last_migration {
+        true => get_schemas_diff_content()?,
+        false => MigrationContent::default(),
+    };
+
+    let migrations_dir = Path::new(MIGRATIONS_DIR).to_path_buf();
+    let down_migrations_dir = migrations_dir.join(DOWN_MIGRATIONS_DIR);
+
+    let file_name = get_migration_file_name(&Local::now(), &name);
+    let migration_file = migrations_dir.join(&file_name);
+    let down_migration_file = down_migrations_dir.join(&file_name);
+
+    ensures_file_does_not_exist(&migration_file)?;
+    if down {
+        ensures_file_does_not_exist(&down_migration_file)?;
+    }
+
+    ensures_folder_exists(&migrations_dir)?;
+    std::fs::write(migration_file, content.up)?;
+
+    println!("Migration {} successfully created", file_name);
+
+    if down {
+        ensures_folder_exists(&down_migrations_dir)?;
+        std::fs::write(down_migration_file, content.down)?;
+
+        println!("Down migration {} successfully created", file_name);
+    }
+
+    Ok(())
+}
+
+/// Name of the forward migration files, sorted by date.
+pub fn list_migration_files() -> Result<Vec<String>> {
+    let migrations_dir = Path::new(MIGRATIONS_DIR);
+    if !migrations_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut names = vec![];
+
+    for entry in std::fs::read_dir(migrations_dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if file_name.ends_with(".down.surql") {
+            continue;
+        }
+        if let Some(name) = file_name.strip_suffix(".surql") {
+            names.push(name.to_string());
+        }
+    }
+
+    names.sort();
+
+    Ok(names)
+}
+
+/// Same convention as the migrations of the project templates, e.g. `20230601_120000_AddPost.surql`.
+fn get_migration_file_name(now: &DateTime<Local>, name: &str) -> String {
+    format!(
+        "{}_{}.surql",
+        now.format("%Y%m%d_%H%M%S"),
+        name.to_case(Case::Pascal)
+    )
+}
+
+/// Statements applying the changes of the `/schemas` files since the definitions saved for the last migration file.
+/// The migration runner saves these definitions each time migrations are applied, so they are only missing the changes
+/// of migrations that were added but not applied yet.
+fn get_schemas_diff_content() -> Result<MigrationContent> {
+    let config = config::load_config()?;
+
+    let definitions_dir = Path::new(MIGRATIONS_DIR).join(DEFINITIONS_DIR);
+    let last_migration = list_migration_files()?.pop();
+    let last_migration_schemas =
+        get_last_migration_schemas(&definitions_dir, last_migration.as_deref())?;
+    let current_schemas = read_current_schemas(Path::new(&config.folders.schemas))?;
+
+    let last_migration_definitions = parse_definitions(&last_migration_schemas)
+        .context("Cannot parse the schemas of the last migration")?;
+    let current_definitions =
+        parse_definitions(&current_schemas).context("Cannot parse the /schemas files")?;
+
+    Ok(diff_definitions(
+        &last_migration_definitions,
+        &current_definitions,
+    ))
+}
+
+/// Rebuild the definition of the last migration from the initial definition and the patch of each migration.
+/// Like the migration runner, the patches of migrations that no longer exist after `last_migration` are ignored.
+fn get_last_migration_schemas(
+    definitions_dir: &Path,
+    last_migration: Option<&str>,
+) -> Result<String> {
+    let initial_definition_path = definitions_dir.join(format!("{}.json", INITIAL_DEFINITION_NAME));
+    if !initial_definition_path.exists() {
+        return Err(anyhow!(
+            "Cannot find {}, apply the migrations first with `ultime migrate up`",
+            initial_definition_path.display()
+        ));
+    }
+
+    let initial_definition = std::fs::read_to_string(&initial_definition_path)?;
+    let initial_definition = serde_json::from_str::<SchemaMigrationDefinition>(&initial_definition)
+        .with_context(|| format!("Cannot parse {}", initial_definition_path.display()))?;
+
+    let mut patch_paths = std::fs::read_dir(definitions_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("json"))
+        .filter(|path| {
+            let name = path.file_stem().and_then(|stem| stem.to_str());
+            match (name, last_migration) {
+                (Some(INITIAL_DEFINITION_NAME), _) => false,
+                (Some(name), Some(last_migration)) => name <= last_migration,
+                _ => false,
+            }
+        })
+        .collect::<Vec<_>>();
+    patch_paths.sort();
+
+    let mut schemas = initial_definition.schemas;
+
+    for patch_path in patch_paths {
+        let definition_diff = std::fs::read_to_string(&patch_path)?;
+        let definition_diff = serde_json::from_str::<DefinitionDiff>(&definition_diff)
+            .with_context(|| format!("Cannot parse {}", patch_path.display()))?;
+
+        if let Some(schemas_diff) = definition_diff.schemas {
+            schemas = apply_patch(&schemas, &schemas_diff)
+                .with_context(|| format!("Cannot apply {}", patch_path.display()))?;
+        }
+    }
+
+    Ok(schemas)
+}
+
+fn apply_patch(text: &str, diff: &str) -> Result<String> {
+    let patch = diffy::Patch::from_str(diff)?;
+    let value = diffy::apply(text, &patch)?;
+
+    Ok(value)
+}
+
+/// Content of the `/schemas` files concatenated by name, like the migration runner does.
+fn read_current_schemas(schemas_dir: &Path) -> Result<String> {
+    let mut file_paths = std::fs::read_dir(schemas_dir)
+        .with_context(|| format!("Cannot read {} folder", schemas_dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    file_paths.sort();
+
+    let contents = file_paths
+        .iter()
+        .map(std::fs::read_to_string)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(contents.join("\n"))
+}
+
+/// `DEFINE` statement of the schemas, with the `REMOVE` statement of what it defines.
+#[derive(Debug)]
+struct Definition {
+    remove: String,
+    define: String,
+}
+
+fn parse_definitions(schemas: &str) -> Result<Vec<Definition>> {
+    if schemas.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let Query(Statements(statements)) = surrealdb::sql::parse(schemas)?;
+
+    let definitions = statements
+        .into_iter()
+        .filter_map(|statement| match statement {
+            Statement::Define(statement) => Some(Definition {
+                remove: get_remove_statement(&statement),
+                define: statement.to_string(),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    Ok(definitions)
+}
+
+fn get_remove_statement(statement: &DefineStatement) -> String {
+    let remove_statement = match statement.clone() {
+        DefineStatement::Namespace(statement) => {
+            RemoveStatement::Namespace(RemoveNamespaceStatement {
+                name: statement.name,
+            })
+        }
+        DefineStatement::Database(statement) => {
+            RemoveStatement::Database(RemoveDatabaseStatement {
+                name: statement.name,
+            })
+        }
+        DefineStatement::Function(statement) => {
+            RemoveStatement::Function(RemoveFunctionStatement {
+                name: statement.name,
+            })
+        }
+        DefineStatement::Login(statement) => RemoveStatement::Login(RemoveLoginStatement {
+            name: statement.name,
+            base: statement.base,
+        }),
+        DefineStatement::Token(statement) => RemoveStatement::Token(RemoveTokenStatement {
+            name: statement.name,
+            base: statement.base,
+        }),
+        DefineStatement::Scope(statement) => RemoveStatement::Scope(RemoveScopeStatement {
+            name: statement.name,
+        }),
+        // 💡 the `REMOVE PARAM` statement of the surrealdb crate is formatted without the `$` it requires
+        DefineStatement::Param(statement) => return format!("REMOVE PARAM ${}", statement.name),
+        DefineStatement::Table(statement) => RemoveStatement::Table(RemoveTableStatement {
+            name: statement.name,
+        }),
+        DefineStatement::Event(statement) => RemoveStatement::Event(RemoveEventStatement {
+            name: statement.name,
+            what: statement.what,
+        }),
+        DefineStatement::Field(statement) => RemoveStatement::Field(RemoveFieldStatement {
+            name: statement.name,
+            what: statement.what,
+        }),
+        DefineStatement::Index(statement) => RemoveStatement::Index(RemoveIndexStatement {
+            name: statement.name,
+            what: statement.what,
+        }),
+    };
+
+    remove_statement.to_string()
+}
+
+/// New and changed definitions are defined again, the ones that no longer exist are removed.
+/// The down migration reverts each statement, in reverse order.
+fn diff_definitions(
+    last_definitions: &[Definition],
+    definitions: &[Definition],
+) -> MigrationContent {
+    let find_last_definition = |remove: &str| {
+        last_definitions
+            .iter()
+            .find(|definition| definition.remove == remove)
+    };
+    let exists = |remove: &str| {
+        definitions
+            .iter()
+            .any(|definition| definition.remove == remove)
+    };
+
+    let mut changes = vec![];
+
+    for definition in definitions {
+        match find_last_definition(&definition.remove) {
+            Some(last_definition) if last_definition.define == definition.define => {}
+            Some(last_definition) => {
+                changes.push((&definition.define, &last_definition.define));
+            }
+            None => changes.push((&definition.define, &definition.remove)),
+        }
+    }
+
+    for last_definition in last_definitions {
+        if !exists(&last_definition.remove) {
+            changes.push((&last_definition.remove, &last_definition.define));
+        }
+    }
+
+    MigrationContent {
+        up: changes.iter().map(|(up, _)| format!("{};\n", up)).collect(),
+        down: changes
+            .iter()
+            .rev()
+            .map(|(_, down)| format!("{};\n", down))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn name_migration_file_with_date() {
+        let now = Local.with_ymd_and_hms(2023, 6, 1, 12, 30, 5).unwrap();
+
+        assert_eq!(
+            get_migration_file_name(&now, "add-tag-to-post"),
+            "20230601_123005_AddTagToPost.surql"
+        );
+    }
+
+    #[test]
+    fn diff_definitions_of_schemas() -> Result<()> {
+        let last_migration_schemas = "DEFINE TABLE post SCHEMAFULL;
+DEFINE FIELD title ON post TYPE string;
+DEFINE FIELD status ON post TYPE string;
+DEFINE FIELD content ON post TYPE string;
+";
+        let current_schemas = "DEFINE TABLE post SCHEMAFULL;
+DEFINE FIELD title ON post TYPE string;
+
+-- tags of the post
+DEFINE FIELD tags ON post TYPE array;
+DEFINE INDEX unique_title ON post COLUMNS title UNIQUE;
+DEFINE FIELD content ON post TYPE string ASSERT $value != NONE;
+";
+
+        let content = diff_definitions(
+            &parse_definitions(last_migration_schemas)?,
+            &parse_definitions(current_schemas)?,
+        );
+
+        assert_eq!(
+            content.up,
+            "DEFINE FIELD tags ON post TYPE array;
+DEFINE INDEX unique_title ON post FIELDS title UNIQUE;
+DEFINE FIELD content ON post TYPE string ASSERT $value != NONE;
+REMOVE FIELD status ON post;
+"
+        );
+        assert_eq!(
+            content.down,
+            "DEFINE FIELD status ON post TYPE string;
+DEFINE FIELD content ON post TYPE string;
+REMOVE INDEX unique_title ON post;
+REMOVE FIELD tags ON post;
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn leave_migration_empty_without_schemas_changes() -> Result<()> {
+        let definitions = parse_definitions("DEFINE TABLE post SCHEMAFULL;\n")?;
+
+        assert_eq!(
+            diff_definitions(&definitions, &definitions),
+            MigrationContent::default()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn remove_param_with_its_prefix() -> Result<()> {
+        let definitions = parse_definitions("DEFINE PARAM $page_size VALUE 10;")?;
+
+        assert_eq!(definitions[0].remove, "REMOVE PARAM $page_size");
+
+        Ok(())
+    }
+
+    #[test]
+    fn rebuild_last_migration_schemas_from_patches() -> Result<()> {
+        let definitions_dir = assert_fs::TempDir::new()?;
+
+        let initial_schemas = "DEFINE TABLE post SCHEMAFULL;\n";
+        let applied_schemas =
+            "DEFINE TABLE post SCHEMAFULL;\nDEFINE FIELD title ON post TYPE string;\n";
+
+        let initial_definition = format!(r#"{{"schemas":{:?},"events":""}}"#, initial_schemas);
+        let patch = diffy::create_patch(initial_schemas, applied_schemas).to_string();
+        let definition_diff = format!(r#"{{"schemas":{:?},"events":null}}"#, patch);
+
+        std::fs::write(definitions_dir.join("_initial.json"), initial_definition)?;
+        std::fs::write(
+            definitions_dir.join("20230601_120000_AddTitle.json"),
+            definition_diff,
+        )?;
+        std::fs::write(
+            definitions_dir.join("20230602_120000_RemovedMigration.json"),
+            "{}",
+        )?;
+
+        let last_migration_schemas =
+            get_last_migration_schemas(&definitions_dir, Some("20230601_120000_AddTitle"))?;
+
+        assert_eq!(last_migration_schemas, applied_schemas);
+
+        definitions_dir.close()?;
+
+        Ok(())
+    }
+}
@@ -2,7 +2,9 @@ use anyhow::{Context, Result};
 use convert_case::{Case, Casing};
 use include_dir::{include_dir, Dir};
 use minijinja::{context, Environment};
-use std::path::{Path, PathBuf};
+use std::path::Path;
+
+use super::common::{ensures_folder_exists, register_generated_module, ModuleDeclaration};
 
 pub fn main(name: String) -> Result<()> {
     let src_dir = Path::new("src");
@@ -22,22 +24,20 @@ pub fn main(name: String) -> Result<()> {
     let page_name = format!("{}-page", name).to_case(Case::Pascal);
     let content = Environment::new().render_str(&template_content, context! { page_name })?;
 
-    let file_name = name.to_case(Case::Snake);
-    let file_name = format!("{}.rs", file_name);
+    let module_name = name.to_case(Case::Snake);
+    let file_name = format!("{}.rs", module_name);
 
     let page_file = components_dir.join(file_name);
 
     std::fs::write(page_file, content)?;
+    register_generated_module(
+        &components_dir,
+        &module_name,
+        ModuleDeclaration::Public,
+        ModuleDeclaration::Private,
+    )?;
 
     println!("Page {} successfully created", name);
 
     Ok(())
 }
-
-fn ensures_folder_exists(dir_path: &PathBuf) -> Result<()> {
-    if !dir_path.exists() {
-        fs_extra::dir::create_all(dir_path, false)?;
-    }
-
-    Ok(())
-}
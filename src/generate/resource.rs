@@ -0,0 +1,547 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use convert_case::{Case, Casing};
+use include_dir::{include_dir, Dir};
+use itertools::Itertools;
+use minijinja::{context, Environment};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::{
+    cli::{GenerateEndpointAuth, GenerateEndpointFromSchemaMethod},
+    config,
+};
+
+use super::{
+    common::{
+        ensures_file_does_not_exist, ensures_folder_exists, register_generated_module,
+        ModuleDeclaration,
+    },
+    db::{get_crud_struct_fields, StructField},
+    endpoint::generate_schema_endpoint_content,
+};
+
+/// Pages of a resource, with the name of their file, the suffix of their component and their route.
+const RESOURCE_PAGES: [(&str, &str, &str); 4] = [
+    ("list", "ListPage", ""),
+    ("create", "CreatePage", "/new"),
+    ("details", "DetailsPage", "/:id"),
+    ("edit", "EditPage", "/:id/edit"),
+];
+
+pub struct GenerateResourceArgs {
+    pub table: String,
+    pub auth: GenerateEndpointAuth,
+}
+
+/// Input of the forms of the create/edit pages, bound to a field of the `db::crud` struct.
+#[derive(Debug, PartialEq, Serialize)]
+struct FormInput {
+    /// Name of the form field, deserialized into the `data` argument of the server function
+    name: String,
+    label: String,
+    /// `text`, `number`, `bool` (select), `hidden` or `unsupported` (left to the user)
+    input_type: String,
+    /// Value of the input on the create page
+    default_value: String,
+    /// Expression of the current value of the record on the edit page
+    value: String,
+    /// Part of the record id, that cannot be changed on the edit page
+    is_id: bool,
+}
+
+pub fn main(args: GenerateResourceArgs) -> Result<()> {
+    let GenerateResourceArgs { table, auth } = args;
+
+    let config = config::load_config()?;
+    let folders = &config.folders;
+
+    let table_name = table.to_case(Case::Snake);
+    let struct_name = table_name.to_case(Case::Pascal);
+
+    // 💡 the crud module of the migration runner table has no create/update/delete functions
+    if table_name == "script_migration" {
+        return Err(anyhow!(
+            "Cannot generate a resource for the script_migration table"
+        ));
+    }
+
+    let schema_file_name = format!("{}.surql", table_name);
+    let schema_file = Path::new(&folders.schemas).join(&schema_file_name);
+    if !schema_file.exists() {
+        return Err(anyhow!("Schema '{}' does not exist", schema_file_name));
+    }
+
+    let fields = get_crud_struct_fields(&schema_file, &table_name)?;
+    let inputs = get_form_inputs(&table_name, &fields);
+
+    let crate_name = get_crate_name()?;
+
+    let src_dir = Path::new("src");
+    let api_dir = src_dir.join("api");
+    let pages_dir = src_dir.join("pages");
+
+    let resource_api_dir = api_dir.join(&table_name);
+    let resource_pages_dir = pages_dir.join(&table_name);
+    let api_mod_file = api_dir.join(format!("{}.rs", table_name));
+    let pages_mod_file = pages_dir.join(format!("{}.rs", table_name));
+
+    for path in [
+        &resource_api_dir,
+        &resource_pages_dir,
+        &api_mod_file,
+        &pages_mod_file,
+    ] {
+        ensures_file_does_not_exist(path)?;
+    }
+
+    let mut endpoints = vec![];
+    for method in GenerateEndpointFromSchemaMethod::value_variants() {
+        let method_name = method
+            .to_possible_value()
+            .context("Cannot get method name")?
+            .get_name()
+            .to_case(Case::Snake);
+        let endpoint_name = format!("{}_{}", method_name, table_name);
+
+        let content = generate_schema_endpoint_content(
+            folders,
+            &endpoint_name,
+            &table_name,
+            method.clone(),
+            auth,
+        )?;
+
+        endpoints.push((endpoint_name, content));
+    }
+
+    let mut pages = vec![];
+    for (page_name, _, _) in RESOURCE_PAGES {
+        let template_name = format!("resource.{}.rs.jinja2", page_name);
+        let template_content = get_template(&template_name)?;
+
+        let content = Environment::new().render_str(
+            &template_content,
+            context! { table_name, struct_name, fields, inputs },
+        )?;
+
+        pages.push((page_name.to_string(), content));
+    }
+
+    ensures_folder_exists(&resource_api_dir)?;
+    for (endpoint_name, content) in &endpoints {
+        std::fs::write(
+            resource_api_dir.join(format!("{}.rs", endpoint_name)),
+            content,
+        )?;
+    }
+    std::fs::write(&api_mod_file, to_mod_file_content(endpoints.iter()))?;
+    register_generated_module(
+        &api_dir,
+        &table_name,
+        ModuleDeclaration::Public,
+        ModuleDeclaration::Public,
+    )?;
+
+    ensures_folder_exists(&resource_pages_dir)?;
+    for (page_name, content) in &pages {
+        std::fs::write(
+            resource_pages_dir.join(format!("{}.rs", page_name)),
+            content,
+        )?;
+    }
+    std::fs::write(&pages_mod_file, to_mod_file_content(pages.iter()))?;
+    register_generated_module(
+        &pages_dir,
+        &table_name,
+        ModuleDeclaration::Public,
+        ModuleDeclaration::Private,
+    )?;
+
+    println!("Resource {} successfully created", table_name);
+    println!();
+    println!("Add the routes of its pages to src/app.rs:");
+    println!();
+    println!("{}", format_routes(&table_name, &struct_name));
+    println!();
+    println!("Register its server functions in src/main.rs:");
+    println!();
+    println!(
+        "{}",
+        format_registrations(&crate_name, &table_name, &endpoints)
+    );
+
+    let unsupported_inputs = inputs
+        .iter()
+        .filter(|input| input.input_type == "unsupported")
+        .map(|input| input.label.as_str())
+        .collect::<Vec<_>>();
+    if !unsupported_inputs.is_empty() {
+        println!();
+        println!(
+            "Add the inputs of the following fields to the create/edit pages, they cannot be read from a text input: {}",
+            unsupported_inputs.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Inputs for the fields that can be read from a form.
+/// The required fields of other types are left to the user, with a placeholder in the forms.
+fn get_form_inputs(table_name: &str, fields: &[StructField]) -> Vec<FormInput> {
+    let mut inputs = vec![];
+
+    for field in fields {
+        let name = &field.name;
+        let value = format!("{}.{}", table_name, name);
+        let is_id = name == "id";
+
+        let input = |input_type: &str, value: String| FormInput {
+            name: format!("data[{}]", name),
+            label: name.to_string(),
+            input_type: input_type.to_string(),
+            default_value: String::new(),
+            value,
+            is_id,
+        };
+
+        match field.type_str.as_str() {
+            "String" => inputs.push(input("text", format!("{}.clone()", value))),
+            "Option<String>" => inputs.push(input(
+                "text",
+                format!("{}.clone().unwrap_or_default()", value),
+            )),
+            "bool" => inputs.push(input("bool", value)),
+            "Option<bool>" => inputs.push(input("bool", format!("{}.unwrap_or_default()", value))),
+            "i64" | "f64" | "rust_decimal::Decimal" => {
+                inputs.push(input("number", format!("{}.to_string()", value)))
+            }
+            "Option<i64>" | "Option<f64>" | "Option<rust_decimal::Decimal>" => inputs.push(input(
+                "number",
+                format!(
+                    "{}.map(|value| value.to_string()).unwrap_or_default()",
+                    value
+                ),
+            )),
+            "chrono::DateTime<chrono::Utc>" => {
+                inputs.push(input("text", format!("{}.to_rfc3339()", value)))
+            }
+            "Option<chrono::DateTime<chrono::Utc>>" => inputs.push(input(
+                "text",
+                format!(
+                    "{}.map(|value| value.to_rfc3339()).unwrap_or_default()",
+                    value
+                ),
+            )),
+            "Thing" => inputs.extend(get_thing_inputs(
+                table_name,
+                name,
+                format!("{}.tb.clone()", value),
+                format!("{}.id.to_raw()", value),
+            )),
+            "Option<Thing>" => inputs.extend(get_thing_inputs(
+                table_name,
+                name,
+                format!(
+                    "{}.as_ref().map(|thing| thing.tb.clone()).unwrap_or_default()",
+                    value
+                ),
+                format!(
+                    "{}.as_ref().map(|thing| thing.id.to_raw()).unwrap_or_default()",
+                    value
+                ),
+            )),
+            // 💡 the server function cannot deserialize `data` without the required fields
+            _ if !field.is_optional => inputs.push(input("unsupported", String::new())),
+            _ => {}
+        }
+    }
+
+    inputs
+}
+
+/// 💡 a record id is deserialized from its table and its id, e.g. `data[author][tb]=user&data[author][id][String]=john`
+fn get_thing_inputs(
+    table_name: &str,
+    name: &str,
+    table_value: String,
+    id_value: String,
+) -> [FormInput; 2] {
+    let is_id = name == "id";
+
+    [
+        FormInput {
+            name: format!("data[{}][tb]", name),
+            label: format!("{} table", name),
+            input_type: match is_id {
+                true => "hidden".to_string(),
+                false => "text".to_string(),
+            },
+            default_value: match is_id {
+                true => table_name.to_string(),
+                false => String::new(),
+            },
+            value: table_value,
+            is_id,
+        },
+        FormInput {
+            name: format!("data[{}][id][String]", name),
+            label: name.to_string(),
+            input_type: "text".to_string(),
+            default_value: String::new(),
+            value: id_value,
+            is_id,
+        },
+    ]
+}
+
+fn to_mod_file_content<'a>(modules: impl Iterator<Item = &'a (String, String)>) -> String {
+    let names = modules.map(|(name, _)| name).sorted().collect::<Vec<_>>();
+
+    let mod_lines = names.iter().map(|name| format!("mod {};", name)).join("\n");
+    let use_lines = names
+        .iter()
+        .map(|name| format!("pub use self::{}::*;", name))
+        .join("\n");
+
+    format!("{}\n\n{}\n", mod_lines, use_lines)
+}
+
+fn format_routes(table_name: &str, struct_name: &str) -> String {
+    let page_names = RESOURCE_PAGES
+        .iter()
+        .map(|(_, suffix, _)| format!("{}{}", struct_name, suffix))
+        .sorted()
+        .join(", ");
+
+    let routes = RESOURCE_PAGES
+        .iter()
+        .map(|(_, suffix, path)| {
+            format!(
+                "<Route path=\"/{}{}\" view=|cx| view! {{ cx, <{}{}/> }}/>",
+                table_name, path, struct_name, suffix
+            )
+        })
+        .join("\n");
+
+    format!(
+        "use crate::pages::{}::{{{}}};\n\n{}",
+        table_name, page_names, routes
+    )
+}
+
+/// `register()` calls of the server functions of a resource, that `src/main.rs` needs to serve them.
+fn format_registrations(
+    crate_name: &str,
+    table_name: &str,
+    endpoints: &[(String, String)],
+) -> String {
+    let server_fn_names = endpoints
+        .iter()
+        .map(|(endpoint_name, _)| endpoint_name.to_case(Case::Pascal))
+        .sorted()
+        .collect::<Vec<_>>();
+
+    let registrations = server_fn_names
+        .iter()
+        .map(|name| format!("let _ = {}::register();", name))
+        .join("\n");
+
+    format!(
+        "use {}::api::{}::{{{}}};\n\n{}",
+        crate_name,
+        table_name,
+        server_fn_names.join(", "),
+        registrations
+    )
+}
+
+/// Name of the library crate of the project, imported by `src/main.rs`.
+fn get_crate_name() -> Result<String> {
+    let content = std::fs::read_to_string("Cargo.toml").context("Cannot read Cargo.toml")?;
+    let manifest = toml::from_str::<toml::Value>(&content).context("Cannot parse Cargo.toml")?;
+
+    let name = ["lib", "package"]
+        .iter()
+        .find_map(|section| manifest.get(section)?.get("name")?.as_str())
+        .context("Cannot get the package name from Cargo.toml")?;
+
+    Ok(name.replace('-', "_"))
+}
+
+fn get_template(template_name: &str) -> Result<String> {
+    const TEMPLATES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates/generate");
+
+    let template_content = TEMPLATES_DIR
+        .get_file(template_name)
+        .context(format!("Cannot get template '{}'", template_name))?
+        .contents_utf8()
+        .context(format!("Cannot get template '{}'", template_name))?
+        .to_string();
+
+    Ok(template_content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn to_struct_fields(fields: Vec<(&str, &str)>) -> Vec<StructField> {
+        fields
+            .into_iter()
+            .map(|(name, type_str)| StructField {
+                name: name.to_string(),
+                type_str: type_str.to_string(),
+                is_optional: type_str.starts_with("Option<"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn get_form_inputs_of_supported_fields() {
+        let fields = to_struct_fields(vec![
+            ("author", "Thing"),
+            ("created_at", "Option<chrono::DateTime<chrono::Utc>>"),
+            ("id", "Thing"),
+            ("is_published", "bool"),
+            ("rating", "Option<f64>"),
+            ("reviewer", "Option<Thing>"),
+            ("tags", "Vec<String>"),
+            ("title", "String"),
+        ]);
+
+        let inputs = get_form_inputs("post", &fields);
+
+        let to_input =
+            |name: &str, label: &str, input_type: &str, default_value: &str, value: &str| {
+                FormInput {
+                    name: name.to_string(),
+                    label: label.to_string(),
+                    input_type: input_type.to_string(),
+                    default_value: default_value.to_string(),
+                    value: value.to_string(),
+                    is_id: name.starts_with("data[id]"),
+                }
+            };
+
+        assert_eq!(
+            inputs,
+            vec![
+                to_input(
+                    "data[author][tb]",
+                    "author table",
+                    "text",
+                    "",
+                    "post.author.tb.clone()"
+                ),
+                to_input(
+                    "data[author][id][String]",
+                    "author",
+                    "text",
+                    "",
+                    "post.author.id.to_raw()"
+                ),
+                to_input(
+                    "data[created_at]",
+                    "created_at",
+                    "text",
+                    "",
+                    "post.created_at.map(|value| value.to_rfc3339()).unwrap_or_default()"
+                ),
+                to_input(
+                    "data[id][tb]",
+                    "id table",
+                    "hidden",
+                    "post",
+                    "post.id.tb.clone()"
+                ),
+                to_input(
+                    "data[id][id][String]",
+                    "id",
+                    "text",
+                    "",
+                    "post.id.id.to_raw()"
+                ),
+                to_input(
+                    "data[is_published]",
+                    "is_published",
+                    "bool",
+                    "",
+                    "post.is_published"
+                ),
+                to_input(
+                    "data[rating]",
+                    "rating",
+                    "number",
+                    "",
+                    "post.rating.map(|value| value.to_string()).unwrap_or_default()"
+                ),
+                to_input(
+                    "data[reviewer][tb]",
+                    "reviewer table",
+                    "text",
+                    "",
+                    "post.reviewer.as_ref().map(|thing| thing.tb.clone()).unwrap_or_default()"
+                ),
+                to_input(
+                    "data[reviewer][id][String]",
+                    "reviewer",
+                    "text",
+                    "",
+                    "post.reviewer.as_ref().map(|thing| thing.id.to_raw()).unwrap_or_default()"
+                ),
+                to_input("data[tags]", "tags", "unsupported", "", ""),
+                to_input("data[title]", "title", "text", "", "post.title.clone()"),
+            ]
+        );
+    }
+
+    #[test]
+    fn format_registrations_of_resource_server_functions() {
+        let endpoints = [
+            ("list_post".to_string(), String::new()),
+            ("create_post".to_string(), String::new()),
+        ];
+
+        assert_eq!(
+            format_registrations("my_blog", "post", &endpoints),
+            "use my_blog::api::post::{CreatePost, ListPost};
+
+let _ = CreatePost::register();
+let _ = ListPost::register();"
+        );
+    }
+
+    #[test]
+    fn format_routes_of_resource_pages() {
+        assert_eq!(
+            format_routes("post", "Post"),
+            r#"use crate::pages::post::{PostCreatePage, PostDetailsPage, PostEditPage, PostListPage};
+
+<Route path="/post" view=|cx| view! { cx, <PostListPage/> }/>
+<Route path="/post/new" view=|cx| view! { cx, <PostCreatePage/> }/>
+<Route path="/post/:id" view=|cx| view! { cx, <PostDetailsPage/> }/>
+<Route path="/post/:id/edit" view=|cx| view! { cx, <PostEditPage/> }/>"#
+        );
+    }
+
+    #[test]
+    fn generate_mod_file_of_sorted_modules() {
+        let modules = [
+            ("list".to_string(), String::new()),
+            ("create".to_string(), String::new()),
+        ];
+
+        assert_eq!(
+            to_mod_file_content(modules.iter()),
+            "mod create;
+mod list;
+
+pub use self::create::*;
+pub use self::list::*;
+"
+        );
+    }
+}
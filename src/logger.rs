@@ -0,0 +1,245 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    fs::{self, File},
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+const LOGS_DIR: &str = "target/ultime/logs";
+const MAX_RECENT_LINES: usize = 1000;
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// Process that wrote a log line, displayed as a prefix when the logs are multiplexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogSource {
+    /// Messages of ultime itself, never prefixed.
+    Ultime,
+    /// SurrealDB instance
+    Db,
+    /// `cargo leptos watch`
+    App,
+    /// Generation of the `db` module
+    Gen,
+}
+
+impl LogSource {
+    pub fn prefix(&self) -> Option<&'static str> {
+        match self {
+            LogSource::Ultime => None,
+            LogSource::Db => Some("[db]"),
+            LogSource::App => Some("[app]"),
+            LogSource::Gen => Some("[gen]"),
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self {
+            LogSource::Ultime => "",
+            LogSource::Db => "\x1b[35m",
+            LogSource::App => "\x1b[36m",
+            LogSource::Gen => "\x1b[33m",
+        }
+    }
+}
+
+pub struct LoggerOptions {
+    /// Hide the logs of the SurrealDB instance, they are still written to the log file.
+    pub quiet_db: bool,
+}
+
+/// A line logged during the session, kept to be displayed by the dashboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub time: DateTime<Local>,
+    pub source: LogSource,
+    pub text: String,
+    pub is_error: bool,
+}
+
+struct Logger {
+    quiet_db: bool,
+    colored: bool,
+    file: Mutex<File>,
+    /// Disabled while the dashboard is displayed, the lines are only kept in memory.
+    terminal_output: AtomicBool,
+    recent_lines: Mutex<VecDeque<LogLine>>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Multiplex the logs of every process of the dev server: each line is prefixed with its source
+/// and written to a log file of the session, under `target/ultime/logs`.
+/// Until then, the logs are printed as is.
+pub fn init(options: LoggerOptions) -> Result<PathBuf> {
+    fs::create_dir_all(LOGS_DIR)?;
+
+    let file_name = format!("{}.log", Local::now().format("%Y%m%d_%H%M%S"));
+    let file_path = Path::new(LOGS_DIR).join(file_name);
+    let file = File::create(&file_path)?;
+
+    let colored = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+
+    let logger = Logger {
+        quiet_db: options.quiet_db,
+        colored,
+        file: Mutex::new(file),
+        terminal_output: AtomicBool::new(true),
+        recent_lines: Mutex::new(VecDeque::new()),
+    };
+
+    LOGGER
+        .set(logger)
+        .map_err(|_| anyhow!("Logger is already initialized"))?;
+
+    Ok(file_path)
+}
+
+/// Print the logs on the terminal, or only keep them in memory (e.g. while the dashboard is displayed).
+pub fn set_terminal_output(enabled: bool) {
+    if let Some(logger) = LOGGER.get() {
+        logger.terminal_output.store(enabled, Ordering::SeqCst);
+    }
+}
+
+/// Last lines logged during the session, without the hidden ones (e.g. SurrealDB logs with `--quiet-db`).
+pub fn recent_lines() -> Vec<LogLine> {
+    let Some(logger) = LOGGER.get() else {
+        return vec![];
+    };
+
+    let recent_lines = logger.recent_lines.lock().unwrap();
+    recent_lines
+        .iter()
+        .filter(|line| logger.is_visible(line.source))
+        .cloned()
+        .collect()
+}
+
+pub fn log(source: LogSource, message: impl Display) {
+    write(source, message, false);
+}
+
+pub fn log_error(source: LogSource, message: impl Display) {
+    write(source, message, true);
+}
+
+fn write(source: LogSource, message: impl Display, is_error: bool) {
+    let Some(logger) = LOGGER.get() else {
+        match is_error {
+            true => eprintln!("{}", message),
+            false => println!("{}", message),
+        }
+        return;
+    };
+
+    for line in message.to_string().lines() {
+        logger.write_line(source, line, is_error);
+    }
+}
+
+impl Logger {
+    fn write_line(&self, source: LogSource, line: &str, is_error: bool) {
+        let time = Local::now();
+
+        if let Ok(mut file) = self.file.lock() {
+            let formatted_time = time.format("%H:%M:%S%.3f");
+            let _ = writeln!(
+                file,
+                "{} {}",
+                formatted_time,
+                format_line(source, line, false)
+            );
+        }
+
+        if let Ok(mut recent_lines) = self.recent_lines.lock() {
+            if recent_lines.len() == MAX_RECENT_LINES {
+                recent_lines.pop_front();
+            }
+            recent_lines.push_back(LogLine {
+                time,
+                source,
+                text: line.to_string(),
+                is_error,
+            });
+        }
+
+        if !self.is_visible(source) || !self.terminal_output.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let line = format_line(source, line, self.colored);
+        match is_error {
+            true => eprintln!("{}", line),
+            false => println!("{}", line),
+        }
+    }
+
+    fn is_visible(&self, source: LogSource) -> bool {
+        !(self.quiet_db && source == LogSource::Db)
+    }
+}
+
+/// Prefixes are aligned so that the logs of every process are easy to read.
+fn format_line(source: LogSource, line: &str, colored: bool) -> String {
+    match (source.prefix(), colored) {
+        (None, _) => line.to_string(),
+        (Some(prefix), true) => format!("{}{:<5}{} {}", source.color(), prefix, RESET_COLOR, line),
+        (Some(prefix), false) => format!("{:<5} {}", prefix, line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn align_prefixes_of_log_lines() {
+        assert_eq!(
+            format_line(LogSource::Db, "Started web server", false),
+            "[db]  Started web server"
+        );
+        assert_eq!(
+            format_line(LogSource::App, "Compiling blog", false),
+            "[app] Compiling blog"
+        );
+        assert_eq!(
+            format_line(LogSource::Ultime, "Start leptos app...", false),
+            "Start leptos app..."
+        );
+    }
+
+    #[test]
+    fn color_prefix_only() {
+        assert_eq!(
+            format_line(LogSource::Gen, "db folder up to date", true),
+            "\x1b[33m[gen]\x1b[0m db folder up to date"
+        );
+    }
+
+    #[test]
+    fn hide_db_logs_when_quiet() -> Result<()> {
+        let temp_file = assert_fs::NamedTempFile::new("ultime.log")?;
+
+        let logger = Logger {
+            quiet_db: true,
+            colored: false,
+            file: Mutex::new(File::create(temp_file.path())?),
+            terminal_output: AtomicBool::new(true),
+            recent_lines: Mutex::new(VecDeque::new()),
+        };
+
+        assert!(!logger.is_visible(LogSource::Db));
+        assert!(logger.is_visible(LogSource::App));
+        assert!(logger.is_visible(LogSource::Ultime));
+
+        Ok(())
+    }
+}
@@ -1,12 +1,19 @@
 use anyhow::Result;
 use clap::Parser;
 use cli::{Action, GenerateAction};
-use generate::endpoint::GenerateEndpointArgs;
+use generate::{
+    endpoint::GenerateEndpointArgs, migration::GenerateMigrationArgs,
+    resource::GenerateResourceArgs,
+};
+use run::RunArgs;
 
 use crate::cli::Args;
 
 mod cli;
+mod config;
 mod generate;
+mod logger;
+mod migrate;
 mod new;
 mod run;
 
@@ -15,13 +22,37 @@ async fn main() -> Result<()> {
     let args = Args::parse();
 
     match args.command {
-        None => run::main(args.open).await,
+        None => {
+            let args = RunArgs {
+                open: args.open,
+                storage: args.storage,
+                db_engine: args.db_engine,
+                reset: args.reset,
+                port: args.port,
+                db_port: args.db_port,
+                quiet_db: args.quiet_db,
+                no_tui: args.no_tui,
+            };
+            run::main(args).await
+        }
         Some(command) => match command {
             Action::New { name, template } => new::main(name, template),
             Action::Generate { command } => match command {
-                GenerateAction::Db { watch } => generate::db::main(watch),
+                GenerateAction::Db { watch, check } => generate::db::main(watch, check),
                 GenerateAction::Component { name } => generate::component::main(name),
                 GenerateAction::Page { name } => generate::page::main(name),
+                GenerateAction::Migration {
+                    name,
+                    down,
+                    diff_from_last_migration,
+                } => {
+                    let args = GenerateMigrationArgs {
+                        name,
+                        down,
+                        diff_from_last_migration,
+                    };
+                    generate::migration::main(args)
+                }
                 GenerateAction::Endpoint {
                     name,
                     from_query,
@@ -29,6 +60,7 @@ async fn main() -> Result<()> {
                     from_event,
                     from_schema,
                     method,
+                    auth,
                 } => {
                     let args = GenerateEndpointArgs {
                         name,
@@ -37,10 +69,16 @@ async fn main() -> Result<()> {
                         from_event,
                         from_schema,
                         method,
+                        auth,
                     };
                     generate::endpoint::main(args)
                 }
+                GenerateAction::Resource { table, auth } => {
+                    let args = GenerateResourceArgs { table, auth };
+                    generate::resource::main(args)
+                }
             },
+            Action::Migrate { command } => migrate::main(command).await,
         },
     }
 }
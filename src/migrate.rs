@@ -0,0 +1,309 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use surrealdb::{
+    engine::remote::ws::{Client, Ws},
+    opt::auth::Root,
+    Surreal,
+};
+use surrealdb_migrations::MigrationRunner;
+
+use crate::{
+    cli::MigrateAction,
+    config,
+    generate::{
+        self,
+        migration::{
+            list_migration_files, GenerateMigrationArgs, DOWN_MIGRATIONS_DIR, MIGRATIONS_DIR,
+        },
+    },
+};
+
+/// Target of the migration runner to revert every migration.
+const INITIAL_MIGRATION: &str = "0";
+
+/// Migration applied to the database, stored in the `script_migration` table by the migration runner.
+#[derive(Debug, Clone, PartialEq)]
+struct AppliedMigration {
+    name: String,
+    executed_at: String,
+}
+
+pub async fn main(action: MigrateAction) -> Result<()> {
+    match action {
+        MigrateAction::Up { to } => up(&connect().await?, to).await,
+        MigrateAction::Down { to } => down(&connect().await?, to).await,
+        MigrateAction::Status => status(&connect().await?).await,
+        MigrateAction::List => list(&connect().await?).await,
+        MigrateAction::New { name, down } => {
+            let args = GenerateMigrationArgs {
+                name,
+                down,
+                diff_from_last_migration: false,
+            };
+            generate::migration::main(args)
+        }
+    }
+}
+
+/// Connect to the database configured in `ultime.toml`, usually the instance started by `ultime`.
+async fn connect() -> Result<Surreal<Client>> {
+    let config = config::load_config()?;
+    let surrealdb_config = &config.surrealdb;
+
+    let db = Surreal::new::<Ws>(surrealdb_config.address.as_str())
+        .await
+        .with_context(|| {
+            format!(
+                "Cannot connect to SurrealDB on {}, is `ultime` running?",
+                surrealdb_config.address
+            )
+        })?;
+
+    db.signin(Root {
+        username: &surrealdb_config.username,
+        password: &surrealdb_config.password,
+    })
+    .await?;
+
+    db.use_ns(&surrealdb_config.ns)
+        .use_db(&surrealdb_config.db)
+        .await?;
+
+    Ok(db)
+}
+
+async fn up(db: &Surreal<Client>, to: Option<String>) -> Result<()> {
+    let applied_before = list_applied_migrations(db).await?;
+    let runner = MigrationRunner::new(db);
+
+    let result = match &to {
+        Some(name) => {
+            ensures_migration_exists(name)?;
+            runner.up_to(name).await
+        }
+        None => runner.up().await,
+    };
+    result.map_err(|error| anyhow!("Cannot apply migrations: {:#}", error))?;
+
+    let applied_after = list_applied_migrations(db).await?;
+    let applied = applied_after
+        .iter()
+        .filter(|migration| !applied_before.contains(migration))
+        .collect::<Vec<_>>();
+
+    if applied.is_empty() {
+        println!("Migrations are up to date");
+    }
+    for migration in applied {
+        println!("Migration {} applied", migration.name);
+    }
+
+    Ok(())
+}
+
+/// Revert the migrations applied after `to`, or the last applied migration.
+async fn down(db: &Surreal<Client>, to: Option<String>) -> Result<()> {
+    let applied_before = list_applied_migrations(db).await?;
+
+    let target = match to {
+        Some(name) => {
+            ensures_migration_exists(&name)?;
+            name
+        }
+        None => match applied_before.iter().rev().nth(1) {
+            Some(migration) => migration.name.to_string(),
+            None => INITIAL_MIGRATION.to_string(),
+        },
+    };
+
+    let to_revert = applied_before
+        .iter()
+        .filter(|migration| migration.name > target)
+        .collect::<Vec<_>>();
+
+    if to_revert.is_empty() {
+        println!("No migration to revert");
+        return Ok(());
+    }
+
+    // 💡 the migration runner silently skips a migration without down file, leaving it applied
+    for migration in &to_revert {
+        if find_down_migration_file(&migration.name).is_none() {
+            return Err(anyhow!(
+                "Cannot revert migration {}, there is no down migration file in {}",
+                migration.name,
+                Path::new(MIGRATIONS_DIR)
+                    .join(DOWN_MIGRATIONS_DIR)
+                    .display()
+            ));
+        }
+    }
+
+    MigrationRunner::new(db)
+        .down(&target)
+        .await
+        .map_err(|error| anyhow!("Cannot revert migrations: {:#}", error))?;
+
+    for migration in to_revert.iter().rev() {
+        println!("Migration {} reverted", migration.name);
+    }
+
+    Ok(())
+}
+
+async fn status(db: &Surreal<Client>) -> Result<()> {
+    let applied = list_applied_migrations(db).await?;
+    let files = list_migration_files()?;
+
+    println!("{}", format_status_table(&files, &applied));
+
+    Ok(())
+}
+
+async fn list(db: &Surreal<Client>) -> Result<()> {
+    let applied = list_applied_migrations(db).await?;
+
+    if applied.is_empty() {
+        println!("No migration applied");
+        return Ok(());
+    }
+
+    for migration in applied {
+        println!("{}  {}", migration.name, migration.executed_at);
+    }
+
+    Ok(())
+}
+
+async fn list_applied_migrations(db: &Surreal<Client>) -> Result<Vec<AppliedMigration>> {
+    let migrations = MigrationRunner::new(db)
+        .list()
+        .await
+        .map_err(|error| anyhow!("Cannot list applied migrations: {:#}", error))?;
+
+    let migrations = migrations
+        .into_iter()
+        .map(|migration| AppliedMigration {
+            name: migration.script_name,
+            executed_at: migration.executed_at,
+        })
+        .collect();
+
+    Ok(migrations)
+}
+
+fn ensures_migration_exists(name: &str) -> Result<()> {
+    match list_migration_files()?.iter().any(|file| file == name) {
+        true => Ok(()),
+        false => Err(anyhow!(
+            "Migration {} not found in {} folder",
+            name,
+            MIGRATIONS_DIR
+        )),
+    }
+}
+
+fn find_down_migration_file(name: &str) -> Option<PathBuf> {
+    let migrations_dir = Path::new(MIGRATIONS_DIR);
+
+    [
+        migrations_dir
+            .join(DOWN_MIGRATIONS_DIR)
+            .join(format!("{}.surql", name)),
+        migrations_dir.join(format!("{}.down.surql", name)),
+    ]
+    .into_iter()
+    .find(|path| path.exists())
+}
+
+/// Every migration file and every applied migration, with its status.
+/// An applied migration whose file has been deleted is marked as `missing`.
+fn format_status_table(files: &[String], applied: &[AppliedMigration]) -> String {
+    let mut rows = files
+        .iter()
+        .map(
+            |name| match applied.iter().find(|migration| &migration.name == name) {
+                Some(migration) => (name.as_str(), "applied", migration.executed_at.as_str()),
+                None => (name.as_str(), "pending", ""),
+            },
+        )
+        .collect::<Vec<_>>();
+
+    rows.extend(
+        applied
+            .iter()
+            .filter(|migration| !files.contains(&migration.name))
+            .map(|migration| {
+                (
+                    migration.name.as_str(),
+                    "missing",
+                    migration.executed_at.as_str(),
+                )
+            }),
+    );
+    rows.sort_by_key(|(name, _, _)| *name);
+
+    if rows.is_empty() {
+        return "No migration found".to_string();
+    }
+
+    let name_width = rows
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .chain(std::iter::once("Migration".len()))
+        .max()
+        .unwrap_or_default();
+
+    std::iter::once(("Migration", "Status", "Applied at"))
+        .chain(rows)
+        .map(|(name, status, executed_at)| {
+            format!(
+                "{:<name_width$}  {:<7}  {}",
+                name,
+                status,
+                executed_at,
+                name_width = name_width
+            )
+            .trim_end()
+            .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn format_status_of_each_migration() {
+        let files = vec![
+            "20230601_120000_AddAdminUser".to_string(),
+            "20230601_120001_AddPost".to_string(),
+        ];
+        let applied = vec![
+            AppliedMigration {
+                name: "20230601_120000_AddAdminUser".to_string(),
+                executed_at: "2023-06-02T08:00:00Z".to_string(),
+            },
+            AppliedMigration {
+                name: "20230530_090000_RemovedMigration".to_string(),
+                executed_at: "2023-06-01T08:00:00Z".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            format_status_table(&files, &applied),
+            "Migration                         Status   Applied at
+20230530_090000_RemovedMigration  missing  2023-06-01T08:00:00Z
+20230601_120000_AddAdminUser      applied  2023-06-02T08:00:00Z
+20230601_120001_AddPost           pending"
+        );
+    }
+
+    #[test]
+    fn format_status_without_migrations() {
+        assert_eq!(format_status_table(&[], &[]), "No migration found");
+    }
+}
@@ -9,7 +9,10 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::cli::UltimeProjectTemplate;
+use crate::{
+    cli::UltimeProjectTemplate,
+    generate::migration::{DOWN_MIGRATIONS_DIR, MIGRATIONS_DIR},
+};
 
 pub fn main(name: String, template: Option<UltimeProjectTemplate>) -> Result<()> {
     let template = match template {
@@ -149,7 +152,7 @@ fn create_project_dir(from: &Dir, to: &Path, name: &str) -> Result<()> {
         println!("Creating migration project...");
 
         // Rename files in migrations folder
-        let migrations_dir_path = to.join("migrations");
+        let migrations_dir_path = to.join(MIGRATIONS_DIR);
         if migrations_dir_path.exists() {
             let regex = Regex::new(r"^YYYYMMDD_HHMM(\d{2})_")?;
 
@@ -193,7 +196,7 @@ fn create_project_dir(from: &Dir, to: &Path, name: &str) -> Result<()> {
         }
 
         // Rename files in migrations/down folder
-        let down_migrations_dir_path = migrations_dir_path.join("down");
+        let down_migrations_dir_path = migrations_dir_path.join(DOWN_MIGRATIONS_DIR);
         if down_migrations_dir_path.exists() {
             let regex = Regex::new(r"^YYYYMMDD_HHMM(\d{2})_")?;
 
@@ -1,112 +1,263 @@
 use anyhow::{anyhow, Result};
 use std::{
+    io::IsTerminal,
+    net::SocketAddr,
     path::Path,
-    process::{Command, Stdio},
-    time::Duration,
+    process::{Child, Command, Stdio},
 };
-use surrealdb::{engine::remote::ws::Ws, opt::auth::Root};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 
-use crate::generate;
+use crate::{
+    config::{self, SurrealdbEngine, SurrealdbStorage, UltimeConfig, SURREALDB_ADDRESS_ENV},
+    generate::{self, db::SharedGenerationStatus},
+    logger::{self, LogSource, LoggerOptions},
+};
+
+mod dashboard;
+mod definitions;
+mod logs;
+mod ports;
+mod supervisor;
+mod surrealdb;
+
+use dashboard::{
+    BuildStatus, Dashboard, DashboardCommand, DashboardInfo, DbKind, SharedBuildStatus,
+};
+use logs::forward_logs_with;
+use ports::{Ports, RequestedPorts};
+use supervisor::{isolate_process_group, RestartPolicy, Supervisor};
+
+const DB_PROCESS_NAME: &str = "SurrealDB instance";
+const APP_PROCESS_NAME: &str = "leptos app";
+
+pub struct RunArgs {
+    pub open: bool,
+    pub storage: Option<SurrealdbStorage>,
+    pub db_engine: Option<SurrealdbEngine>,
+    pub reset: bool,
+    pub port: Option<u16>,
+    pub db_port: Option<u16>,
+    pub quiet_db: bool,
+    pub no_tui: bool,
+}
+
+pub async fn main(args: RunArgs) -> Result<()> {
+    let RunArgs {
+        open,
+        storage,
+        db_engine,
+        reset,
+        port,
+        db_port,
+        quiet_db,
+        no_tui,
+    } = args;
 
-pub async fn main(open: bool) -> Result<()> {
     if !is_valid_ultime_project() {
         return Err(anyhow!("This is not a valid ultime project"));
     }
 
-    start_surrealdb_instance().await?;
-    generate::db::generate_db_folder()?;
-    start_leptos_app(open)?;
+    let log_file = logger::init(LoggerOptions { quiet_db })?;
+    logger::log(
+        LogSource::Ultime,
+        format_args!("Logs are written to {}", log_file.display()),
+    );
 
-    // 💡 prevent watcher to be dropped
-    let _watcher = generate::db::watch_to_regenerate_db_folder()?;
+    let mut config = config::load_config()?;
 
-    // 💡 infinite loop to keep the process alive
-    loop {
-        std::thread::sleep(Duration::from_secs(1));
+    if let Some(storage) = storage {
+        config.surrealdb.storage = storage;
+    }
+    if let Some(db_engine) = db_engine {
+        config.surrealdb.engine = db_engine;
     }
-}
-
-fn is_valid_ultime_project() -> bool {
-    let has_cargo_toml = Path::new("Cargo.toml").exists();
-    has_cargo_toml
-}
 
-async fn start_surrealdb_instance() -> Result<()> {
-    let check_surreal_cli = Command::new("surreal")
-        .arg("-h")
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?
-        .wait()?;
+    let requested_ports = RequestedPorts {
+        app: port,
+        db: db_port,
+    };
+    let ports = ports::select_ports(&config, requested_ports)?;
 
-    if check_surreal_cli.success() {
-        println!("Start a new SurrealDB instance...");
+    config.surrealdb.set_port(ports.db);
+    config.app.set_port(ports.app);
 
-        let username = "root";
-        let password = "root";
+    let mut supervisor = Supervisor::new(RestartPolicy::default());
 
-        let _start_surreal_instance = Command::new("surreal")
-            .arg("start")
-            .arg("--user")
-            .arg(username)
-            .arg("--pass")
-            .arg(password)
-            .arg("memory")
-            .spawn()?;
+    let db_kind = match surrealdb::start_instance(&config, reset).await? {
+        surrealdb::Instance::Cli(surreal_instance) => {
+            let config = config.clone();
+            supervisor.add(DB_PROCESS_NAME, surreal_instance, move || {
+                let config = config.clone();
+                async move { surrealdb::restart_cli_instance(&config).await }
+            });
+            DbKind::Cli(DB_PROCESS_NAME)
+        }
+        surrealdb::Instance::Embedded => DbKind::Embedded,
+        surrealdb::Instance::Skipped => DbKind::Skipped,
+    };
 
-        // TODO : Read error from child process
+    // 💡 prevent watcher to be dropped, broken .surql files are reported and fixed while the app is running
+    let generation_status = SharedGenerationStatus::default();
+    let _watcher = generate::db::watch_to_regenerate_db_folder(&config, &generation_status)?;
 
-        // TODO : Wait until SurrealDB instance is ready
-        println!("SurrealDB instance started successfully");
+    let build_status = SharedBuildStatus::default();
+    let leptos_app = start_leptos_app(&config, ports, &build_status, open)?;
+    let leptos_config = config.clone();
+    let leptos_build_status = build_status.clone();
+    supervisor.add(APP_PROCESS_NAME, leptos_app, move || {
+        let leptos_app = spawn_leptos_app(&leptos_config, ports, &leptos_build_status);
+        async { leptos_app }
+    });
 
-        std::thread::sleep(Duration::from_secs(1)); // TODO : Wait until SurrealDB instance is ready
+    let _definitions_watcher = match db_kind {
+        DbKind::Skipped => None,
+        _ => Some(definitions::watch_to_apply_definitions(&config)?),
+    };
 
-        // Try to apply migrations on the SurrealDB instance
-        let db = surrealdb::Surreal::new::<Ws>("localhost:8000").await?;
+    let use_dashboard =
+        !no_tui && std::io::stdout().is_terminal() && std::io::stdin().is_terminal();
+    if !use_dashboard {
+        return supervisor.run(std::future::pending()).await;
+    }
 
-        db.signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await?;
+    let (commands_sender, commands) = mpsc::unbounded_channel();
+    let info = DashboardInfo {
+        db_address: config.surrealdb.address.to_string(),
+        db_kind,
+        app_url: config.app.url(),
+        app_process_name: APP_PROCESS_NAME,
+        statuses: supervisor.statuses(),
+        build_status,
+        generation_status: generation_status.clone(),
+    };
+    let dashboard = Dashboard::start(info, commands_sender)?;
 
-        db.use_ns("test").use_db("test").await?;
+    let result = supervisor
+        .run(handle_dashboard_commands(
+            commands,
+            config,
+            generation_status,
+        ))
+        .await;
 
-        println!("Start to apply SurrealDB migrations...");
+    // 💡 restore the terminal before the error is printed
+    drop(dashboard);
 
-        let result = surrealdb_migrations::MigrationRunner::new(&db).up().await;
+    result
+}
 
-        match result {
-            Ok(_) => println!("SurrealDB migrations applied successfully"),
-            Err(err) => println!("SurrealDB migrations failed to apply: {:?}", err),
+/// Execute the actions requested from the dashboard until the user quits.
+async fn handle_dashboard_commands(
+    mut commands: UnboundedReceiver<DashboardCommand>,
+    config: UltimeConfig,
+    generation_status: SharedGenerationStatus,
+) {
+    while let Some(command) = commands.recv().await {
+        match command {
+            DashboardCommand::Regenerate => {
+                let config = config.clone();
+                let generation_status = generation_status.clone();
+                tokio::task::spawn_blocking(move || {
+                    logger::log(LogSource::Ultime, "Regenerate db folder...");
+                    if let Err(error) =
+                        generate::db::generate_db_folder(&config, &generation_status)
+                    {
+                        logger::log_error(
+                            LogSource::Gen,
+                            format_args!("Error while generating db folder: {:#}", error),
+                        );
+                    }
+                });
+            }
+            DashboardCommand::ApplyMigrations => {
+                let config = config.clone();
+                tokio::spawn(async move {
+                    if let Err(error) = surrealdb::reapply_migrations(&config).await {
+                        logger::log_error(
+                            LogSource::Ultime,
+                            format_args!("Cannot apply SurrealDB migrations: {:#}", error),
+                        );
+                    }
+                });
+            }
+            DashboardCommand::OpenBrowser => {
+                if let Err(error) = open_app_in_browser(&config) {
+                    logger::log_error(
+                        LogSource::Ultime,
+                        format_args!("Cannot open the app in browser: {:#}", error),
+                    );
+                }
+            }
+            DashboardCommand::Quit => return,
         }
-    } else {
-        println!("surreal cli does not seem to be installed. Step skipped...");
     }
 
-    Ok(())
+    // 💡 the dashboard stopped by itself, keep running without it
+    std::future::pending::<()>().await
 }
 
-fn start_leptos_app(open: bool) -> Result<()> {
-    println!("Start leptos app...");
-    let _start_leptos_app = Command::new("cargo").arg("leptos").arg("watch").spawn()?;
+fn is_valid_ultime_project() -> bool {
+    let has_cargo_toml = Path::new("Cargo.toml").exists();
+    has_cargo_toml
+}
 
-    // TODO : Handle error if port (3000) already in use
-    // TODO : Use a different port if 3000 is already in use
+fn start_leptos_app(
+    config: &UltimeConfig,
+    ports: Ports,
+    build_status: &SharedBuildStatus,
+    open: bool,
+) -> Result<Child> {
+    logger::log(
+        LogSource::Ultime,
+        format_args!("Start leptos app on {}...", config.app.url()),
+    );
+    let leptos_app = spawn_leptos_app(config, ports, build_status)?;
 
     if open {
-        open_app_in_browser()?;
+        open_app_in_browser(config)?;
     }
 
-    Ok(())
+    Ok(leptos_app)
+}
+
+fn spawn_leptos_app(
+    config: &UltimeConfig,
+    ports: Ports,
+    build_status: &SharedBuildStatus,
+) -> Result<Child> {
+    // 💡 override `site-addr` and `reload-port` of Cargo.toml with the selected ports
+    let mut leptos_app = isolate_process_group(
+        Command::new("cargo")
+            .arg("leptos")
+            .arg("watch")
+            .env(
+                "LEPTOS_SITE_ADDR",
+                SocketAddr::new(config.app.bind_host(), ports.app).to_string(),
+            )
+            .env("LEPTOS_RELOAD_PORT", ports.reload.to_string())
+            .env(SURREALDB_ADDRESS_ENV, &config.surrealdb.address)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )
+    .spawn()?;
+
+    // 💡 a restarted app is built again from scratch
+    *build_status.lock().unwrap() = BuildStatus::Waiting;
+    let build_status = build_status.clone();
+    forward_logs_with(&mut leptos_app, LogSource::App, move |line| {
+        let mut build_status = build_status.lock().unwrap();
+        *build_status = build_status.next(line);
+    });
+
+    Ok(leptos_app)
 }
 
-fn open_app_in_browser() -> Result<()> {
-    const APP_URL: &str = "http://localhost:3000";
+fn open_app_in_browser(config: &UltimeConfig) -> Result<()> {
+    let app_url = config.app.url();
 
-    println!("Opening app in browser...");
-    open::that(APP_URL)?;
+    logger::log(LogSource::Ultime, "Opening app in browser...");
+    open::that(app_url)?;
 
     Ok(())
 }
@@ -0,0 +1,427 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame, Terminal,
+};
+use std::{
+    io::{stdout, Stdout},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    generate::db::{GenerationStatus, SharedGenerationStatus},
+    logger::{self, LogLine, LogSource},
+};
+
+use super::supervisor::{ProcessStatus, ProcessStatuses};
+
+const TICK_DURATION: Duration = Duration::from_millis(200);
+
+/// Action requested from the dashboard with a key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DashboardCommand {
+    Regenerate,
+    ApplyMigrations,
+    OpenBrowser,
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DbKind {
+    /// Supervised `surreal` process, named after the given process name.
+    Cli(&'static str),
+    Embedded,
+    Skipped,
+}
+
+/// What the dashboard displays, besides the logs of the session.
+pub struct DashboardInfo {
+    pub db_address: String,
+    pub db_kind: DbKind,
+    pub app_url: String,
+    pub app_process_name: &'static str,
+    pub statuses: ProcessStatuses,
+    pub build_status: SharedBuildStatus,
+    pub generation_status: SharedGenerationStatus,
+}
+
+/// Status of the last build of `cargo leptos watch`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BuildStatus {
+    #[default]
+    Waiting,
+    Building,
+    Failed,
+    Ready,
+}
+
+impl BuildStatus {
+    /// Status after a line of the output of `cargo leptos watch`, lines that do not tell anything about the build keep the current status.
+    pub fn next(self, line: &str) -> Self {
+        let text = line.trim_start();
+
+        if text.starts_with("error") {
+            Self::Failed
+        } else if text.starts_with("Compiling") {
+            Self::Building
+        } else if text.starts_with("Finished") || text.contains("listening on") {
+            Self::Ready
+        } else {
+            self
+        }
+    }
+}
+
+/// Build status updated by the reader of the logs of the leptos app, shared with the dashboard.
+pub type SharedBuildStatus = Arc<Mutex<BuildStatus>>;
+
+/// Terminal dashboard of the dev server, displayed until it is dropped.
+pub struct Dashboard {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Dashboard {
+    pub fn start(info: DashboardInfo, commands: UnboundedSender<DashboardCommand>) -> Result<Self> {
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen)?;
+        logger::set_terminal_output(false);
+
+        let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                let result = run_dashboard(terminal, &info, &commands, &stop);
+
+                restore_terminal();
+
+                if let Err(error) = result {
+                    logger::log_error(
+                        LogSource::Ultime,
+                        format_args!("Dashboard stopped: {:#}", error),
+                    );
+                }
+            })
+        };
+
+        Ok(Self {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(stdout(), LeaveAlternateScreen);
+    logger::set_terminal_output(true);
+}
+
+fn run_dashboard(
+    mut terminal: Terminal<CrosstermBackend<Stdout>>,
+    info: &DashboardInfo,
+    commands: &UnboundedSender<DashboardCommand>,
+    stop: &AtomicBool,
+) -> Result<()> {
+    while !stop.load(Ordering::SeqCst) {
+        let lines = logger::recent_lines();
+        terminal.draw(|frame| draw(frame, info, &lines))?;
+
+        if !event::poll(TICK_DURATION)? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            let command = match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Some(DashboardCommand::Quit)
+                }
+                code => to_command(code),
+            };
+
+            if let Some(command) = command {
+                let _ = commands.send(command);
+            }
+        }
+    }
+
+    terminal.show_cursor()?;
+
+    Ok(())
+}
+
+fn to_command(code: KeyCode) -> Option<DashboardCommand> {
+    match code {
+        KeyCode::Char('r') => Some(DashboardCommand::Regenerate),
+        KeyCode::Char('m') => Some(DashboardCommand::ApplyMigrations),
+        KeyCode::Char('o') => Some(DashboardCommand::OpenBrowser),
+        KeyCode::Char('q') | KeyCode::Esc => Some(DashboardCommand::Quit),
+        _ => None,
+    }
+}
+
+fn draw<B: Backend>(frame: &mut Frame<B>, info: &DashboardInfo, lines: &[LogLine]) {
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+
+    let status = Paragraph::new(vec![
+        db_status_line(info),
+        app_status_line(info),
+        generation_status_line(info.generation_status.lock().unwrap().clone()),
+    ])
+    .block(Block::default().borders(Borders::ALL).title(" ultime "));
+    frame.render_widget(status, areas[0]);
+
+    let visible_lines = areas[1].height.saturating_sub(2) as usize;
+    let log_lines = lines
+        .iter()
+        .skip(lines.len().saturating_sub(visible_lines))
+        .map(to_log_line)
+        .collect::<Vec<_>>();
+    let logs =
+        Paragraph::new(log_lines).block(Block::default().borders(Borders::ALL).title(" logs "));
+    frame.render_widget(logs, areas[1]);
+
+    let help = Paragraph::new(Line::from(vec![
+        key_span("r"),
+        Span::raw(" regenerate db  "),
+        key_span("m"),
+        Span::raw(" apply migrations  "),
+        key_span("o"),
+        Span::raw(" open browser  "),
+        key_span("q"),
+        Span::raw(" quit"),
+    ]));
+    frame.render_widget(help, areas[2]);
+}
+
+fn key_span(key: &str) -> Span<'_> {
+    Span::styled(key, Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn label_span(label: &str) -> Span<'_> {
+    Span::styled(
+        format!("{:<11}", label),
+        Style::default().add_modifier(Modifier::BOLD),
+    )
+}
+
+fn status_span(text: String, color: Color) -> Span<'static> {
+    Span::styled(format!("● {}", text), Style::default().fg(color))
+}
+
+fn process_status(info: &DashboardInfo, name: &str) -> Option<ProcessStatus> {
+    info.statuses.lock().unwrap().get(name).copied()
+}
+
+fn db_status_line(info: &DashboardInfo) -> Line<'_> {
+    let status = match info.db_kind {
+        DbKind::Cli(name) => match process_status(info, name) {
+            Some(ProcessStatus::Running) => {
+                status_span("running (surreal cli)".into(), Color::Green)
+            }
+            Some(ProcessStatus::Restarting) => status_span("restarting".into(), Color::Yellow),
+            _ => status_span("stopped".into(), Color::Red),
+        },
+        DbKind::Embedded => status_span("running (embedded)".into(), Color::Green),
+        DbKind::Skipped => status_span("not started".into(), Color::DarkGray),
+    };
+
+    Line::from(vec![
+        label_span("SurrealDB"),
+        Span::raw(format!("{}  ", info.db_address)),
+        status,
+    ])
+}
+
+fn app_status_line(info: &DashboardInfo) -> Line<'_> {
+    let status = match process_status(info, info.app_process_name) {
+        Some(ProcessStatus::Restarting) => status_span("restarting".into(), Color::Yellow),
+        Some(ProcessStatus::Stopped) | None => status_span("stopped".into(), Color::Red),
+        Some(ProcessStatus::Running) => match *info.build_status.lock().unwrap() {
+            BuildStatus::Waiting => status_span("starting".into(), Color::DarkGray),
+            BuildStatus::Building => status_span("building".into(), Color::Yellow),
+            BuildStatus::Failed => status_span("build failed".into(), Color::Red),
+            BuildStatus::Ready => status_span("ready".into(), Color::Green),
+        },
+    };
+
+    Line::from(vec![
+        label_span("App"),
+        Span::raw(format!("{}  ", info.app_url)),
+        status,
+    ])
+}
+
+fn generation_status_line(generation_status: Option<GenerationStatus>) -> Line<'static> {
+    let format_time = |time: DateTime<Local>| time.format("%H:%M:%S").to_string();
+
+    let status = match generation_status {
+        None => status_span("not generated yet".into(), Color::DarkGray),
+        Some(GenerationStatus::Generated { time, changes }) => status_span(
+            format!("generated at {} ({} changes)", format_time(time), changes),
+            Color::Green,
+        ),
+        Some(GenerationStatus::UpToDate { time }) => {
+            status_span(format!("up to date at {}", format_time(time)), Color::Green)
+        }
+        Some(GenerationStatus::Failed { time, message }) => status_span(
+            format!("failed at {}: {}", format_time(time), message),
+            Color::Red,
+        ),
+    };
+
+    Line::from(vec![label_span("db module"), status])
+}
+
+fn to_log_line(line: &LogLine) -> Line<'static> {
+    let mut spans = vec![];
+
+    if let Some(prefix) = line.source.prefix() {
+        let color = match line.source {
+            LogSource::Db => Color::Magenta,
+            LogSource::App => Color::Cyan,
+            LogSource::Gen => Color::Yellow,
+            LogSource::Ultime => Color::Reset,
+        };
+        spans.push(Span::styled(
+            format!("{:<5} ", prefix),
+            Style::default().fg(color),
+        ));
+    }
+
+    let style = match line.is_error && line.source == LogSource::Ultime {
+        true => Style::default().fg(Color::Red),
+        false => Style::default(),
+    };
+    spans.push(Span::styled(line.text.clone(), style));
+
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use ratatui::backend::TestBackend;
+
+    fn log_line(source: LogSource, text: &str) -> LogLine {
+        LogLine {
+            time: Local::now(),
+            source,
+            text: text.to_string(),
+            is_error: false,
+        }
+    }
+
+    #[test]
+    fn map_keys_to_commands() {
+        assert_eq!(
+            to_command(KeyCode::Char('r')),
+            Some(DashboardCommand::Regenerate)
+        );
+        assert_eq!(
+            to_command(KeyCode::Char('m')),
+            Some(DashboardCommand::ApplyMigrations)
+        );
+        assert_eq!(
+            to_command(KeyCode::Char('o')),
+            Some(DashboardCommand::OpenBrowser)
+        );
+        assert_eq!(to_command(KeyCode::Char('q')), Some(DashboardCommand::Quit));
+        assert_eq!(to_command(KeyCode::Char('x')), None);
+    }
+
+    #[test]
+    fn update_build_status_from_app_logs() {
+        let status = BuildStatus::default()
+            .next("   Compiling blog v0.1.0")
+            .next("warning: unused variable: `x`");
+
+        assert_eq!(status, BuildStatus::Building);
+        assert_eq!(
+            status.next("error[E0425]: cannot find value `x` in this scope"),
+            BuildStatus::Failed
+        );
+        assert_eq!(
+            status.next("    Finished dev [unoptimized + debuginfo] target(s) in 2.35s"),
+            BuildStatus::Ready
+        );
+    }
+
+    #[test]
+    fn draw_status_of_each_process() {
+        let info = DashboardInfo {
+            db_address: "localhost:8000".to_string(),
+            db_kind: DbKind::Embedded,
+            app_url: "http://localhost:3000".to_string(),
+            app_process_name: "leptos app",
+            statuses: ProcessStatuses::default(),
+            build_status: Arc::new(Mutex::new(BuildStatus::Building)),
+            generation_status: Arc::new(Mutex::new(Some(GenerationStatus::UpToDate {
+                time: Local::now(),
+            }))),
+        };
+        info.statuses
+            .lock()
+            .unwrap()
+            .insert("leptos app", ProcessStatus::Running);
+
+        let lines = vec![log_line(LogSource::App, "Compiling blog v0.1.0")];
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|frame| draw(frame, &info, &lines)).unwrap();
+
+        let content = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol.as_str())
+            .collect::<String>();
+
+        assert!(content.contains("SurrealDB  localhost:8000  ● running (embedded)"));
+        assert!(content.contains("App        http://localhost:3000  ● building"));
+        assert!(content.contains("db module  ● up to date at"));
+        assert!(content.contains("[app] Compiling blog v0.1.0"));
+        assert!(content.contains("r regenerate db  m apply migrations  o open browser  q quit"));
+    }
+}
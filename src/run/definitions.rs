@@ -0,0 +1,263 @@
+use anyhow::Result;
+use itertools::Itertools;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use crate::{
+    config::UltimeConfig,
+    generate::{self, migration::MIGRATIONS_DIR},
+    logger::{self, LogSource},
+};
+
+use super::surrealdb;
+
+const WATCH_DEBOUNCE_DURATION: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum DefinitionKind {
+    Schemas,
+    Events,
+    Migrations,
+}
+
+struct WatchedDir {
+    kind: DefinitionKind,
+    /// Folder as configured, used to display the paths relative to the project
+    folder: PathBuf,
+    canonical_dir: PathBuf,
+}
+
+/// Changes to push to the running SurrealDB instance after a burst of file events.
+#[derive(Debug, PartialEq)]
+struct DefinitionChanges {
+    /// Schemas files first, then events files, so that events are defined on existing tables.
+    files_to_apply: Vec<PathBuf>,
+    removed_files: Vec<PathBuf>,
+    has_migration_changes: bool,
+}
+
+/// Keep the running SurrealDB instance in sync with the `/schemas`, `/events` and `/migrations` folders,
+/// so that `ultime` does not need to be restarted after a change.
+pub fn watch_to_apply_definitions(config: &UltimeConfig) -> Result<RecommendedWatcher> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |result| {
+        let _ = sender.send(result);
+    })?;
+
+    let folders_to_watch = [
+        (DefinitionKind::Schemas, config.folders.schemas.as_str()),
+        (DefinitionKind::Events, config.folders.events.as_str()),
+        (DefinitionKind::Migrations, MIGRATIONS_DIR),
+    ];
+
+    let mut watched_dirs = vec![];
+
+    for (kind, folder) in folders_to_watch {
+        let dir = Path::new(folder);
+        if dir.exists() {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+            watched_dirs.push(WatchedDir {
+                kind,
+                folder: dir.to_path_buf(),
+                canonical_dir: dir.canonicalize()?,
+            });
+        }
+    }
+
+    tokio::spawn(apply_on_changes(config.clone(), watched_dirs, receiver));
+
+    Ok(watcher)
+}
+
+/// Wait for the end of a burst of file events, then apply the changed definitions.
+/// The task ends when the watcher is dropped.
+async fn apply_on_changes(
+    config: UltimeConfig,
+    watched_dirs: Vec<WatchedDir>,
+    mut receiver: UnboundedReceiver<notify::Result<notify::Event>>,
+) {
+    while let Some(result) = receiver.recv().await {
+        let mut results = vec![result];
+        while let Ok(Some(result)) =
+            tokio::time::timeout(WATCH_DEBOUNCE_DURATION, receiver.recv()).await
+        {
+            results.push(result);
+        }
+
+        let changed_file_paths = results
+            .into_iter()
+            .filter_map(|result| match result {
+                Ok(event) => Some(event),
+                Err(error) => {
+                    logger::log_error(LogSource::Ultime, format_args!("Watch error: {}", error));
+                    None
+                }
+            })
+            .filter(|event| generate::db::is_file_change(&event.kind))
+            .flat_map(|event| event.paths)
+            .collect::<Vec<_>>();
+
+        let changes = get_definition_changes(&watched_dirs, &changed_file_paths);
+
+        for file_path in &changes.removed_files {
+            logger::log(
+                LogSource::Ultime,
+                format_args!(
+                    "{} removed, its definitions are kept until the SurrealDB instance is reset",
+                    file_path.display()
+                ),
+            );
+        }
+
+        let result = match changes {
+            // 💡 the migration runner applies every schemas and events files before the new migrations
+            DefinitionChanges {
+                has_migration_changes: true,
+                ..
+            } => surrealdb::reapply_migrations(&config).await,
+            DefinitionChanges { files_to_apply, .. } if !files_to_apply.is_empty() => {
+                surrealdb::apply_definition_files(&config, &files_to_apply).await
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(error) = result {
+            logger::log_error(
+                LogSource::Ultime,
+                format_args!("Cannot apply changes to SurrealDB instance: {:#}", error),
+            );
+        }
+    }
+}
+
+fn get_definition_changes(
+    watched_dirs: &[WatchedDir],
+    file_paths: &[PathBuf],
+) -> DefinitionChanges {
+    let changed_files = file_paths
+        .iter()
+        .filter(|file_path| {
+            file_path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                == Some("surql")
+        })
+        .filter_map(|file_path| {
+            let dir = file_path.parent()?.canonicalize().ok()?;
+            let watched_dir = watched_dirs
+                .iter()
+                .find(|watched_dir| watched_dir.canonical_dir == dir)?;
+            let relative_path = watched_dir.folder.join(file_path.file_name()?);
+
+            Some((watched_dir.kind, relative_path, file_path.exists()))
+        })
+        .unique()
+        .sorted()
+        .collect::<Vec<_>>();
+
+    let has_migration_changes = changed_files
+        .iter()
+        .any(|(kind, _, _)| *kind == DefinitionKind::Migrations);
+
+    let (files_to_apply, removed_files) = changed_files
+        .into_iter()
+        .filter(|(kind, _, _)| *kind != DefinitionKind::Migrations)
+        .partition_map(|(_, path, exists)| match exists {
+            true => itertools::Either::Left(path),
+            false => itertools::Either::Right(path),
+        });
+
+    DefinitionChanges {
+        files_to_apply,
+        removed_files,
+        has_migration_changes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{prelude::*, TempDir};
+    use pretty_assertions::assert_eq;
+
+    fn create_watched_dirs(dir: &TempDir) -> Vec<WatchedDir> {
+        [
+            (DefinitionKind::Schemas, "schemas"),
+            (DefinitionKind::Events, "events"),
+            (DefinitionKind::Migrations, "migrations"),
+        ]
+        .into_iter()
+        .map(|(kind, folder)| {
+            let child_dir = dir.child(folder);
+            child_dir.create_dir_all().unwrap();
+
+            WatchedDir {
+                kind,
+                folder: PathBuf::from(folder),
+                canonical_dir: child_dir.canonicalize().unwrap(),
+            }
+        })
+        .collect()
+    }
+
+    #[test]
+    fn apply_schemas_before_events() {
+        let dir = TempDir::new().unwrap();
+        let watched_dirs = create_watched_dirs(&dir);
+        dir.child("events/publish_post.surql").touch().unwrap();
+        dir.child("schemas/post.surql").touch().unwrap();
+
+        let changes = get_definition_changes(
+            &watched_dirs,
+            &[
+                dir.join("events/publish_post.surql"),
+                dir.join("schemas/post.surql"),
+                dir.join("schemas/post.surql"),
+                dir.join("schemas/post.surql.swp"),
+            ],
+        );
+
+        assert_eq!(
+            changes,
+            DefinitionChanges {
+                files_to_apply: vec![
+                    PathBuf::from("schemas/post.surql"),
+                    PathBuf::from("events/publish_post.surql")
+                ],
+                removed_files: vec![],
+                has_migration_changes: false,
+            }
+        );
+    }
+
+    #[test]
+    fn detect_removed_files_and_migration_changes() {
+        let dir = TempDir::new().unwrap();
+        let watched_dirs = create_watched_dirs(&dir);
+        dir.child("migrations/20230601_120000_AddPost.surql")
+            .touch()
+            .unwrap();
+
+        let changes = get_definition_changes(
+            &watched_dirs,
+            &[
+                dir.join("schemas/removed_table.surql"),
+                dir.join("migrations/20230601_120000_AddPost.surql"),
+            ],
+        );
+
+        assert_eq!(
+            changes,
+            DefinitionChanges {
+                files_to_apply: vec![],
+                removed_files: vec![PathBuf::from("schemas/removed_table.surql")],
+                has_migration_changes: true,
+            }
+        );
+    }
+}
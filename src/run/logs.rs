@@ -0,0 +1,127 @@
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read},
+    process::Child,
+    sync::{Arc, Mutex},
+};
+
+use crate::logger::{self, LogSource};
+
+const MAX_RECENT_LOGS: usize = 20;
+
+/// Last lines written by a child process, used to explain why it stopped.
+#[derive(Debug, Clone, Default)]
+pub struct RecentLogs(Arc<Mutex<VecDeque<String>>>);
+
+impl RecentLogs {
+    fn push(&self, line: String) {
+        let mut lines = self.0.lock().unwrap();
+
+        if lines.len() == MAX_RECENT_LOGS {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Log each line of stdout/stderr of a child process with the prefix of its source, e.g. `[db] Started web server on 0.0.0.0:8000`.
+/// The child must be spawned with piped stdout/stderr.
+pub fn forward_logs(child: &mut Child, source: LogSource) -> RecentLogs {
+    forward_logs_with(child, source, |_| {})
+}
+
+/// Same as [`forward_logs`], but also pass each line to `on_line`, e.g. to track the status of a build.
+pub fn forward_logs_with(
+    child: &mut Child,
+    source: LogSource,
+    on_line: impl Fn(&str) + Send + Sync + 'static,
+) -> RecentLogs {
+    let recent_logs = RecentLogs::default();
+    let on_line: LineHandler = Arc::new(on_line);
+
+    if let Some(stdout) = child.stdout.take() {
+        spawn_log_reader(stdout, source, recent_logs.clone(), on_line.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_log_reader(stderr, source, recent_logs.clone(), on_line);
+    }
+
+    recent_logs
+}
+
+type LineHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+fn spawn_log_reader<R: Read + Send + 'static>(
+    reader: R,
+    source: LogSource,
+    recent_logs: RecentLogs,
+    on_line: LineHandler,
+) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+
+            match is_error_line(&line) {
+                true => logger::log_error(source, &line),
+                false => logger::log(source, &line),
+            }
+            on_line(&line);
+            recent_logs.push(line);
+        }
+    });
+}
+
+/// 💡 stderr is not only used for errors (e.g. the progress of cargo), only the lines reporting an error are highlighted
+fn is_error_line(line: &str) -> bool {
+    line.trim_start().starts_with("error")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn keep_last_lines_only() {
+        let recent_logs = RecentLogs::default();
+
+        for index in 0..25 {
+            recent_logs.push(format!("line {}", index));
+        }
+
+        let lines = recent_logs.lines();
+
+        assert_eq!(lines.len(), MAX_RECENT_LOGS);
+        assert_eq!(lines.first(), Some(&"line 5".to_string()));
+        assert_eq!(lines.last(), Some(&"line 24".to_string()));
+    }
+
+    #[test]
+    fn detect_error_lines() {
+        let lines = [
+            "   Compiling ultime v0.0.5",
+            "error[E0425]: cannot find value `x` in this scope",
+            "error: could not compile `blog` due to previous error",
+            "warning: unused variable: `error`",
+        ];
+
+        let errors = lines
+            .into_iter()
+            .filter(|line| is_error_line(line))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                "error[E0425]: cannot find value `x` in this scope",
+                "error: could not compile `blog` due to previous error",
+            ]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{anyhow, Context, Result};
+use std::net::TcpListener;
+
+use crate::{
+    config::UltimeConfig,
+    logger::{self, LogSource},
+};
+
+/// Number of ports tried after the configured one before giving up.
+const MAX_PORT_ATTEMPTS: u16 = 100;
+
+/// Ports used by `ultime`, each of them available when selected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ports {
+    pub app: u16,
+    /// Port of the websocket used by `cargo leptos watch` to reload the browser.
+    pub reload: u16,
+    pub db: u16,
+}
+
+/// Ports explicitly requested by the user, they are never replaced.
+#[derive(Debug, Default)]
+pub struct RequestedPorts {
+    pub app: Option<u16>,
+    pub db: Option<u16>,
+}
+
+/// Use the requested ports or the configured ones, or the next available ports if they are already in use
+/// (e.g. when another ultime project is running).
+pub fn select_ports(config: &UltimeConfig, requested: RequestedPorts) -> Result<Ports> {
+    let configured_db_port = config
+        .surrealdb
+        .port()
+        .context("Cannot retrieve SurrealDB port from address")?;
+    let configured_app_port = config
+        .app
+        .port()
+        .context("Cannot retrieve app port from address")?;
+
+    let db = select_port("SurrealDB instance", requested.db, configured_db_port, &[])?;
+    let app = select_port("leptos app", requested.app, configured_app_port, &[db])?;
+    let reload = select_port("leptos reload", None, app.saturating_add(1), &[db, app])?;
+
+    Ok(Ports { app, reload, db })
+}
+
+fn select_port(
+    name: &str,
+    requested: Option<u16>,
+    configured: u16,
+    reserved: &[u16],
+) -> Result<u16> {
+    if let Some(port) = requested {
+        if reserved.contains(&port) {
+            return Err(anyhow!(
+                "Cannot start {}, port {} is already used by ultime",
+                name,
+                port
+            ));
+        }
+        ensures_port_is_available(name, port)?;
+        return Ok(port);
+    }
+
+    let is_available = |port: &u16| !reserved.contains(port) && is_port_available(*port);
+
+    if is_available(&configured) {
+        return Ok(configured);
+    }
+
+    let port = (configured.saturating_add(1)..=configured.saturating_add(MAX_PORT_ATTEMPTS))
+        .find(is_available)
+        .ok_or_else(|| {
+            anyhow!(
+                "Cannot start {}, no port available between {} and {}",
+                name,
+                configured,
+                configured.saturating_add(MAX_PORT_ATTEMPTS)
+            )
+        })?;
+
+    logger::log(
+        LogSource::Ultime,
+        format_args!(
+            "Port {} is already in use, {} will listen on port {}",
+            configured, name, port
+        ),
+    );
+
+    Ok(port)
+}
+
+fn is_port_available(port: u16) -> bool {
+    TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+/// Another process listening on the same port would be mistaken for the one started by `ultime`.
+fn ensures_port_is_available(name: &str, port: u16) -> Result<()> {
+    match is_port_available(port) {
+        true => Ok(()),
+        false => Err(anyhow!(
+            "Cannot start {}, port {} is already in use",
+            name,
+            port
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn config_with_ports(app: u16, db: u16) -> UltimeConfig {
+        let mut config = UltimeConfig::default();
+        config.app.set_port(app);
+        config.surrealdb.set_port(db);
+        config
+    }
+
+    fn find_free_port() -> u16 {
+        TcpListener::bind(("0.0.0.0", 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[test]
+    fn fails_when_port_is_already_in_use() {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let error = ensures_port_is_available("SurrealDB instance", port).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Cannot start SurrealDB instance, port {} is already in use",
+                port
+            )
+        );
+    }
+
+    #[test]
+    fn select_next_port_when_configured_one_is_in_use() {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let busy_port = listener.local_addr().unwrap().port();
+
+        let port = select_port("leptos app", None, busy_port, &[]).unwrap();
+
+        assert_ne!(port, busy_port);
+        assert!(port > busy_port);
+    }
+
+    #[test]
+    fn fails_when_requested_port_is_in_use() {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let busy_port = listener.local_addr().unwrap().port();
+
+        let config = config_with_ports(find_free_port(), find_free_port());
+        let requested = RequestedPorts {
+            app: Some(busy_port),
+            db: None,
+        };
+
+        let error = select_ports(&config, requested).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Cannot start leptos app, port {} is already in use",
+                busy_port
+            )
+        );
+    }
+
+    #[test]
+    fn never_select_the_same_port_twice() {
+        let port = find_free_port();
+        let config = config_with_ports(port, port);
+
+        let ports = select_ports(&config, RequestedPorts::default()).unwrap();
+
+        assert_eq!(ports.db, port);
+        assert_ne!(ports.app, ports.db);
+        assert_ne!(ports.reload, ports.app);
+        assert_ne!(ports.reload, ports.db);
+    }
+}
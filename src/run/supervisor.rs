@@ -0,0 +1,487 @@
+use anyhow::{anyhow, Result};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    process::{Child, Command},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::logger::{self, LogSource};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+type RestartFuture = Pin<Box<dyn Future<Output = Result<Child>>>>;
+type RestartFn = Box<dyn FnMut() -> RestartFuture>;
+
+/// Restart in progress, with the index of the restarted process.
+type PendingRestart = Option<(usize, RestartFuture)>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessStatus {
+    Running,
+    /// Waiting for the backoff delay before restarting a crashed process.
+    Restarting,
+    Stopped,
+}
+
+/// Status of each supervised process by name, updated while the supervisor is running.
+pub type ProcessStatuses = Arc<Mutex<BTreeMap<&'static str, ProcessStatus>>>;
+
+/// How a crashed child process is restarted.
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    /// Number of consecutive crashes after which the child is no longer restarted.
+    pub max_restarts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// A child running for this duration is considered stable, its crashes count is reset.
+    pub stable_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            stable_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RestartPolicy {
+    fn backoff(&self, crashes: u32) -> Duration {
+        let factor = 2u32.saturating_pow(crashes.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+struct SupervisedProcess {
+    name: &'static str,
+    child: Option<Child>,
+    restart: RestartFn,
+    started_at: Instant,
+    crashes: u32,
+    restart_at: Option<Instant>,
+}
+
+/// Owns the child processes started by `ultime`: restarts them when they crash
+/// and stops them when `ultime` receives SIGINT/SIGTERM or stops.
+pub struct Supervisor {
+    processes: Vec<SupervisedProcess>,
+    policy: RestartPolicy,
+    statuses: ProcessStatuses,
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self {
+            processes: vec![],
+            policy,
+            statuses: ProcessStatuses::default(),
+        }
+    }
+
+    pub fn statuses(&self) -> ProcessStatuses {
+        self.statuses.clone()
+    }
+
+    fn set_status(&self, name: &'static str, status: ProcessStatus) {
+        self.statuses.lock().unwrap().insert(name, status);
+    }
+
+    /// Supervise a running child, `restart` is called to start it again after a crash.
+    pub fn add<F, Fut>(&mut self, name: &'static str, child: Child, mut restart: F)
+    where
+        F: FnMut() -> Fut + 'static,
+        Fut: Future<Output = Result<Child>> + 'static,
+    {
+        self.set_status(name, ProcessStatus::Running);
+        self.processes.push(SupervisedProcess {
+            name,
+            child: Some(child),
+            restart: Box::new(move || Box::pin(restart())),
+            started_at: Instant::now(),
+            crashes: 0,
+            restart_at: None,
+        });
+    }
+
+    /// Keep the children alive until a shutdown signal is received, `stop` completes or a child cannot be restarted.
+    /// The children are always stopped before returning.
+    pub async fn run(mut self, stop: impl Future<Output = ()>) -> Result<()> {
+        let shutdown_signal = wait_for_shutdown_signal();
+        tokio::pin!(shutdown_signal);
+        tokio::pin!(stop);
+
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        // 💡 a restart can take a while (e.g. waiting for SurrealDB to be ready): it is polled
+        // along with the shutdown signals, so that ultime can still be stopped meanwhile
+        let mut pending_restart: PendingRestart = None;
+
+        let result = loop {
+            tokio::select! {
+                signal = &mut shutdown_signal => {
+                    match signal {
+                        Ok(signal) => {
+                            let message = format!("{} received, stopping ultime...", signal);
+                            logger::log(LogSource::Ultime, message);
+                        }
+                        Err(error) => {
+                            let message = format!("Cannot listen to shutdown signals: {:#}", error);
+                            logger::log_error(LogSource::Ultime, message);
+                        }
+                    }
+                    break Ok(());
+                }
+                _ = &mut stop => {
+                    logger::log(LogSource::Ultime, "Stopping ultime...");
+                    break Ok(());
+                }
+                (index, restarted) = wait_for_restart(&mut pending_restart) => {
+                    pending_restart = None;
+                    if let Err(error) = self.on_restarted(index, restarted) {
+                        break Err(error);
+                    }
+                }
+                _ = interval.tick() => {
+                    match self.check_processes(pending_restart.is_none()) {
+                        Ok(Some(restart)) => pending_restart = Some(restart),
+                        Ok(None) => {}
+                        Err(error) => break Err(error),
+                    }
+                }
+            }
+        };
+
+        // 💡 the child of an unfinished restart is stopped when its future is dropped
+        drop(pending_restart);
+        self.shutdown().await;
+
+        result
+    }
+
+    /// Handle the children that stopped, and start the first restart that is due if `can_restart`.
+    fn check_processes(&mut self, can_restart: bool) -> Result<PendingRestart> {
+        let policy = self.policy.clone();
+        let statuses = self.statuses.clone();
+        let set_status = |name, status| statuses.lock().unwrap().insert(name, status);
+
+        for (index, process) in self.processes.iter_mut().enumerate() {
+            if let Some(child) = process.child.as_mut() {
+                if let Some(status) = child.try_wait()? {
+                    process.child = None;
+                    set_status(process.name, ProcessStatus::Stopped);
+                    process.on_crash(&policy, &status.to_string())?;
+                    set_status(process.name, ProcessStatus::Restarting);
+                }
+                continue;
+            }
+
+            let is_restart_due = process
+                .restart_at
+                .is_some_and(|restart_at| restart_at <= Instant::now());
+            if !is_restart_due || !can_restart {
+                continue;
+            }
+
+            logger::log(
+                LogSource::Ultime,
+                format_args!("Restarting {}...", process.name),
+            );
+
+            process.restart_at = None;
+            return Ok(Some((index, (process.restart)())));
+        }
+
+        Ok(None)
+    }
+
+    fn on_restarted(&mut self, index: usize, restarted: Result<Child>) -> Result<()> {
+        let process = &mut self.processes[index];
+
+        match restarted {
+            Ok(child) => {
+                process.child = Some(child);
+                process.started_at = Instant::now();
+                let name = process.name;
+                self.set_status(name, ProcessStatus::Running);
+                Ok(())
+            }
+            Err(error) => process.on_crash(&self.policy, &format!("{:#}", error)),
+        }
+    }
+
+    /// Stop every child gracefully, then kill the ones still running after a timeout.
+    pub async fn shutdown(&mut self) {
+        let mut children = self
+            .processes
+            .iter_mut()
+            .filter_map(|process| process.child.take())
+            .collect::<Vec<_>>();
+
+        for status in self.statuses.lock().unwrap().values_mut() {
+            *status = ProcessStatus::Stopped;
+        }
+
+        for child in children.iter_mut() {
+            terminate(child);
+        }
+
+        let started_at = Instant::now();
+        while started_at.elapsed() < SHUTDOWN_TIMEOUT {
+            children.retain_mut(|child| !matches!(child.try_wait(), Ok(Some(_)) | Err(_)));
+            if children.is_empty() {
+                return;
+            }
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        for child in children.iter_mut() {
+            kill(child);
+        }
+    }
+}
+
+impl SupervisedProcess {
+    fn on_crash(&mut self, policy: &RestartPolicy, reason: &str) -> Result<()> {
+        if self.started_at.elapsed() >= policy.stable_after {
+            self.crashes = 0;
+        }
+        self.crashes += 1;
+
+        if self.crashes > policy.max_restarts {
+            return Err(anyhow!(
+                "{} stopped {} times in a row, giving up (last exit: {})",
+                self.name,
+                self.crashes,
+                reason
+            ));
+        }
+
+        let backoff = policy.backoff(self.crashes);
+        logger::log_error(
+            LogSource::Ultime,
+            format_args!(
+                "{} stopped ({}), restarting in {:.1}s...",
+                self.name,
+                reason,
+                backoff.as_secs_f32()
+            ),
+        );
+
+        self.restart_at = Some(Instant::now() + backoff);
+
+        Ok(())
+    }
+}
+
+impl Drop for Supervisor {
+    /// 💡 never leave orphaned processes holding the ports, even when `ultime` fails to start
+    fn drop(&mut self) {
+        for process in self.processes.iter_mut() {
+            if let Some(child) = process.child.as_mut() {
+                kill(child);
+            }
+        }
+    }
+}
+
+/// Resolve when the pending restart completes, never if there is none.
+async fn wait_for_restart(pending_restart: &mut PendingRestart) -> (usize, Result<Child>) {
+    match pending_restart {
+        Some((index, restart)) => (*index, restart.await),
+        None => std::future::pending().await,
+    }
+}
+
+/// Child killed when dropped, unless it is taken back with `into_inner`.
+/// Used while a child is being started, so that it is not left running when its start is cancelled.
+pub struct KillOnDrop(Option<Child>);
+
+impl KillOnDrop {
+    pub fn new(child: Child) -> Self {
+        Self(Some(child))
+    }
+
+    pub fn into_inner(mut self) -> Child {
+        self.0.take().expect("child is only taken once")
+    }
+}
+
+impl Deref for KillOnDrop {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        self.0.as_ref().expect("child is only taken once")
+    }
+}
+
+impl DerefMut for KillOnDrop {
+    fn deref_mut(&mut self) -> &mut Child {
+        self.0.as_mut().expect("child is only taken once")
+    }
+}
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        if let Some(child) = self.0.as_mut() {
+            kill(child);
+        }
+    }
+}
+
+/// Start the child in its own process group so that the signals are only forwarded by the supervisor,
+/// which can then stop the processes started by the child as well (e.g. the server started by `cargo leptos watch`).
+pub fn isolate_process_group(command: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    command
+}
+
+#[cfg(unix)]
+fn send_signal(child: &Child, signal: i32) {
+    // 💡 a negative pid targets the process group created by `isolate_process_group`
+    unsafe {
+        libc::kill(-(child.id() as i32), signal);
+    }
+}
+
+#[cfg(unix)]
+fn terminate(child: &mut Child) {
+    send_signal(child, libc::SIGTERM);
+}
+
+#[cfg(not(unix))]
+fn terminate(child: &mut Child) {
+    kill(child);
+}
+
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    send_signal(child, libc::SIGKILL);
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(unix)]
+async fn wait_for_shutdown_signal() -> Result<&'static str> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    tokio::select! {
+        _ = interrupt.recv() => Ok("SIGINT"),
+        _ = terminate.recv() => Ok("SIGTERM"),
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_shutdown_signal() -> Result<&'static str> {
+    tokio::signal::ctrl_c().await?;
+    Ok("Ctrl+C")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn spawn_sh(script: &str) -> Result<Child> {
+        let child = isolate_process_group(Command::new("sh").arg("-c").arg(script)).spawn()?;
+        Ok(child)
+    }
+
+    fn fast_policy() -> RestartPolicy {
+        RestartPolicy {
+            max_restarts: 2,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+            stable_after: Duration::from_secs(30),
+        }
+    }
+
+    #[test]
+    fn double_backoff_until_max() {
+        let policy = RestartPolicy::default();
+
+        let backoffs = (1..=7)
+            .map(|crashes| policy.backoff(crashes).as_millis())
+            .collect::<Vec<_>>();
+
+        assert_eq!(backoffs, vec![500, 1000, 2000, 4000, 8000, 10000, 10000]);
+    }
+
+    #[tokio::test]
+    async fn give_up_when_child_keeps_crashing() {
+        let mut supervisor = Supervisor::new(fast_policy());
+
+        let child = spawn_sh("exit 3").unwrap();
+        supervisor.add("crashing app", child, || async { spawn_sh("exit 3") });
+        let statuses = supervisor.statuses();
+
+        let error = supervisor.run(std::future::pending()).await.unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "crashing app stopped 3 times in a row, giving up (last exit: exit status: 3)"
+        );
+        assert_eq!(
+            statuses.lock().unwrap().get("crashing app"),
+            Some(&ProcessStatus::Stopped)
+        );
+    }
+
+    #[tokio::test]
+    async fn stop_children_and_their_processes_on_shutdown() {
+        let mut supervisor = Supervisor::new(fast_policy());
+
+        let child = spawn_sh("sleep 30 & wait").unwrap();
+        let pid = child.id() as i32;
+        supervisor.add("long app", child, || async { spawn_sh("sleep 30") });
+
+        let started_at = Instant::now();
+        supervisor.shutdown().await;
+
+        assert!(started_at.elapsed() < SHUTDOWN_TIMEOUT);
+
+        // 💡 only the direct child is waited for, the other processes of its group exit right after
+        let is_group_alive = || unsafe { libc::kill(-pid, 0) } == 0;
+        while is_group_alive() && started_at.elapsed() < SHUTDOWN_TIMEOUT {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert!(!is_group_alive());
+    }
+
+    #[tokio::test]
+    async fn stop_while_a_restart_is_pending() {
+        let mut supervisor = Supervisor::new(fast_policy());
+
+        let child = spawn_sh("exit 1").unwrap();
+        supervisor.add("slow app", child, || async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            spawn_sh("sleep 30")
+        });
+
+        let started_at = Instant::now();
+        let stop = tokio::time::sleep(Duration::from_millis(500));
+        supervisor.run(stop).await.unwrap();
+
+        assert!(started_at.elapsed() < SHUTDOWN_TIMEOUT);
+    }
+}
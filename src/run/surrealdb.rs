@@ -0,0 +1,392 @@
+use anyhow::{anyhow, Context, Result};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    time::{Duration, Instant},
+};
+use surrealdb::{
+    engine::remote::ws::{Client, Ws},
+    opt::auth::Root,
+    Surreal,
+};
+
+use crate::{
+    config::{SurrealdbConfig, SurrealdbEngine, SurrealdbStorage, UltimeConfig},
+    logger::{self, LogSource},
+};
+
+use super::{
+    logs::{forward_logs, RecentLogs},
+    supervisor::{isolate_process_group, KillOnDrop},
+};
+
+#[cfg(feature = "embedded-db")]
+mod embedded;
+
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+const READY_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// SurrealDB instance started by `ultime`.
+pub enum Instance {
+    /// `surreal` process to supervise
+    Cli(Child),
+    Embedded,
+    /// The `surreal` cli is not installed and the embedded engine is not available.
+    Skipped,
+}
+
+/// Start the SurrealDB instance and apply migrations.
+pub async fn start_instance(config: &UltimeConfig, reset: bool) -> Result<Instance> {
+    let surrealdb_config = &config.surrealdb;
+
+    let engine = match surrealdb_config.engine {
+        SurrealdbEngine::Auto if is_surreal_cli_installed() => SurrealdbEngine::Cli,
+        SurrealdbEngine::Auto if cfg!(feature = "embedded-db") => SurrealdbEngine::Embedded,
+        SurrealdbEngine::Auto => {
+            logger::log(
+                LogSource::Ultime,
+                "surreal cli does not seem to be installed. Step skipped...",
+            );
+            return Ok(Instance::Skipped);
+        }
+        SurrealdbEngine::Cli if !is_surreal_cli_installed() => {
+            return Err(anyhow!("surreal cli does not seem to be installed"));
+        }
+        engine => engine,
+    };
+
+    logger::log(LogSource::Ultime, "Start a new SurrealDB instance...");
+
+    let port = surrealdb_config
+        .port()
+        .context("Cannot retrieve SurrealDB port from address")?;
+
+    let storage = &surrealdb_config.storage;
+    if reset {
+        reset_storage(storage)?;
+    }
+
+    let (db, instance) = match engine {
+        SurrealdbEngine::Embedded => {
+            start_embedded_server(port, surrealdb_config).await?;
+            let db =
+                wait_until_ready(surrealdb_config, || Ok(None), &RecentLogs::default()).await?;
+            (db, Instance::Embedded)
+        }
+        _ => {
+            let (db, surreal_instance) = start_cli_instance(surrealdb_config).await?;
+            (db, Instance::Cli(surreal_instance.into_inner()))
+        }
+    };
+
+    logger::log(LogSource::Ultime, "SurrealDB instance started successfully");
+
+    if let Err(error) = apply_migrations(&db, surrealdb_config).await {
+        // 💡 do not leave the instance running in background when ultime stops
+        if let Instance::Cli(mut surreal_instance) = instance {
+            let _ = surreal_instance.kill();
+            let _ = surreal_instance.wait();
+        }
+        return Err(error);
+    }
+
+    Ok(instance)
+}
+
+/// Apply the migrations that are not applied yet on the running instance (e.g. a new migration file).
+pub async fn reapply_migrations(config: &UltimeConfig) -> Result<()> {
+    let db = Surreal::new::<Ws>(config.surrealdb.address.as_str())
+        .await
+        .context("Cannot connect to the SurrealDB instance")?;
+
+    apply_migrations(&db, &config.surrealdb).await
+}
+
+/// Execute the `DEFINE` statements of schemas/events files on the running instance, without waiting for a restart.
+/// Each file is reported separately so that a broken file does not prevent the others to be applied.
+pub async fn apply_definition_files(config: &UltimeConfig, file_paths: &[PathBuf]) -> Result<()> {
+    let db = Surreal::new::<Ws>(config.surrealdb.address.as_str())
+        .await
+        .context("Cannot connect to the SurrealDB instance")?;
+
+    sign_in(&db, &config.surrealdb).await?;
+
+    for file_path in file_paths {
+        match apply_definition_file(&db, file_path).await {
+            Ok(()) => logger::log(
+                LogSource::Ultime,
+                format_args!("{} applied to SurrealDB instance", file_path.display()),
+            ),
+            Err(error) => logger::log_error(
+                LogSource::Ultime,
+                format_args!("{} failed to apply: {}", file_path.display(), error),
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+async fn apply_definition_file(db: &Surreal<Client>, file_path: &Path) -> Result<()> {
+    let statements = std::fs::read_to_string(file_path)?;
+    db.query(statements).await?.check()?;
+
+    Ok(())
+}
+
+/// Start the `surreal` process again after a crash, migrations are applied in case the data was lost.
+pub async fn restart_cli_instance(config: &UltimeConfig) -> Result<Child> {
+    let (db, surreal_instance) = start_cli_instance(&config.surrealdb).await?;
+
+    logger::log(
+        LogSource::Ultime,
+        "SurrealDB instance restarted successfully",
+    );
+
+    // 💡 the instance is running, failing here would make the supervisor start another one
+    if let Err(error) = apply_migrations(&db, &config.surrealdb).await {
+        logger::log_error(LogSource::Ultime, format_args!("{:#}", error));
+    }
+
+    Ok(surreal_instance.into_inner())
+}
+
+/// 💡 the instance is killed if the start is cancelled (e.g. `ultime` stops during a restart)
+async fn start_cli_instance(
+    surrealdb_config: &SurrealdbConfig,
+) -> Result<(Surreal<Client>, KillOnDrop)> {
+    let port = surrealdb_config
+        .port()
+        .context("Cannot retrieve SurrealDB port from address")?;
+
+    let mut surreal_instance = KillOnDrop::new(
+        isolate_process_group(
+            Command::new("surreal")
+                .arg("start")
+                .arg("--bind")
+                .arg(SocketAddr::new(surrealdb_config.bind_host(), port).to_string())
+                .arg("--user")
+                .arg(&surrealdb_config.username)
+                .arg("--pass")
+                .arg(&surrealdb_config.password)
+                .arg(surrealdb_config.storage.to_surreal_path())
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+        )
+        .spawn()?,
+    );
+
+    let recent_logs = forward_logs(&mut surreal_instance, LogSource::Db);
+
+    let ready = wait_until_ready(
+        surrealdb_config,
+        || Ok(surreal_instance.try_wait()?),
+        &recent_logs,
+    )
+    .await;
+
+    // 💡 an instance that is not ready is not left running in background
+    let db = ready?;
+
+    Ok((db, surreal_instance))
+}
+
+async fn sign_in(db: &Surreal<Client>, surrealdb_config: &SurrealdbConfig) -> Result<()> {
+    db.signin(Root {
+        username: &surrealdb_config.username,
+        password: &surrealdb_config.password,
+    })
+    .await?;
+
+    db.use_ns(&surrealdb_config.ns)
+        .use_db(&surrealdb_config.db)
+        .await?;
+
+    Ok(())
+}
+
+async fn apply_migrations(db: &Surreal<Client>, surrealdb_config: &SurrealdbConfig) -> Result<()> {
+    sign_in(db, surrealdb_config).await?;
+
+    logger::log(LogSource::Ultime, "Start to apply SurrealDB migrations...");
+
+    surrealdb_migrations::MigrationRunner::new(db)
+        .up()
+        .await
+        .map_err(|error| anyhow!("SurrealDB migrations failed to apply: {:#}", error))?;
+
+    logger::log(
+        LogSource::Ultime,
+        "SurrealDB migrations applied successfully",
+    );
+
+    Ok(())
+}
+
+/// Remove the data of a persisted storage, so that migrations are applied on an empty database.
+fn reset_storage(storage: &SurrealdbStorage) -> Result<()> {
+    let Some(path) = storage.path() else {
+        logger::log(
+            LogSource::Ultime,
+            "SurrealDB storage is in memory, nothing to reset",
+        );
+        return Ok(());
+    };
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    // 💡 prevent to wipe anything else than a folder of the project by mistake (e.g. `file:/`)
+    let project_dir = std::env::current_dir()?.canonicalize()?;
+    let storage_dir = path.canonicalize()?;
+    if storage_dir == project_dir || !storage_dir.starts_with(&project_dir) {
+        return Err(anyhow!(
+            "Cannot reset SurrealDB storage '{}', it must be a folder inside the project",
+            path.display()
+        ));
+    }
+
+    logger::log(
+        LogSource::Ultime,
+        format_args!("Reset SurrealDB storage '{}'...", path.display()),
+    );
+
+    match storage_dir.is_dir() {
+        true => std::fs::remove_dir_all(&storage_dir)?,
+        false => std::fs::remove_file(&storage_dir)?,
+    }
+
+    Ok(())
+}
+
+fn is_surreal_cli_installed() -> bool {
+    Command::new("surreal")
+        .arg("-h")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(feature = "embedded-db")]
+async fn start_embedded_server(port: u16, surrealdb_config: &SurrealdbConfig) -> Result<()> {
+    let address = embedded::start_server(port, surrealdb_config).await?;
+    logger::log(
+        LogSource::Db,
+        format_args!("Embedded SurrealDB server listening on {}", address),
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "embedded-db"))]
+async fn start_embedded_server(_port: u16, _surrealdb_config: &SurrealdbConfig) -> Result<()> {
+    Err(anyhow!(
+        "The embedded SurrealDB engine is not available, install ultime with the `embedded-db` feature"
+    ))
+}
+
+/// Retry to connect to the instance until it accepts connections.
+/// `get_exit_status` returns the status of the instance if it stopped before being ready, explained by its `recent_logs`.
+async fn wait_until_ready(
+    surrealdb_config: &SurrealdbConfig,
+    mut get_exit_status: impl FnMut() -> Result<Option<ExitStatus>>,
+    recent_logs: &RecentLogs,
+) -> Result<Surreal<Client>> {
+    let started_at = Instant::now();
+
+    loop {
+        if let Some(status) = get_exit_status()? {
+            return Err(exited_early_error(status, recent_logs).await);
+        }
+
+        let connection = tokio::time::timeout(CONNECT_TIMEOUT, async {
+            Surreal::new::<Ws>(surrealdb_config.address.as_str()).await
+        })
+        .await;
+
+        if let Ok(Ok(db)) = connection {
+            return Ok(db);
+        }
+
+        if started_at.elapsed() >= READY_TIMEOUT {
+            return Err(anyhow!(
+                "SurrealDB instance is not ready after {} seconds, cannot connect to {}",
+                READY_TIMEOUT.as_secs(),
+                surrealdb_config.address
+            ));
+        }
+
+        tokio::time::sleep(READY_RETRY_INTERVAL).await;
+    }
+}
+
+async fn exited_early_error(status: ExitStatus, recent_logs: &RecentLogs) -> anyhow::Error {
+    // 💡 give some time to the log readers to collect the last lines
+    tokio::time::sleep(READY_RETRY_INTERVAL).await;
+
+    let lines = recent_logs.lines();
+
+    match lines.is_empty() {
+        true => anyhow!("SurrealDB instance exited early ({})", status),
+        false => anyhow!(
+            "SurrealDB instance exited early ({}):\n{}",
+            status,
+            lines.join("\n")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuse_to_reset_storage_outside_of_the_project() {
+        let storage = SurrealdbStorage::File("/".into());
+
+        let error = reset_storage(&storage).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Cannot reset SurrealDB storage '/', it must be a folder inside the project"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn fails_when_instance_exits_early() {
+        let mut surreal_instance = Command::new("sh")
+            .arg("-c")
+            .arg("echo 'Address already in use' >&2; exit 1")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let recent_logs = forward_logs(&mut surreal_instance, LogSource::Db);
+        let surrealdb_config = SurrealdbConfig {
+            address: "localhost:1".to_string(),
+            ..Default::default()
+        };
+
+        let error = wait_until_ready(
+            &surrealdb_config,
+            || Ok(surreal_instance.try_wait()?),
+            &recent_logs,
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "SurrealDB instance exited early (exit status: 1):\nAddress already in use"
+        );
+    }
+}
@@ -0,0 +1,907 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    mem,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use surrealdb::{
+    dbs::{Auth, Response, Session},
+    kvs::Datastore,
+    sql::{
+        statements::{
+            CreateStatement, DefineScopeStatement, DeleteStatement, SelectStatement,
+            UpdateStatement,
+        },
+        Array, Data, Field, Fields, Ident, Object, Output, Query, Statement, Statements, Strand,
+        Thing, Value, Values,
+    },
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::Notify,
+};
+use tokio_tungstenite::tungstenite::{
+    handshake::server::{ErrorResponse, Request, Response as HandshakeResponse},
+    http::StatusCode,
+    Message,
+};
+
+use crate::{
+    config::SurrealdbConfig,
+    logger::{self, LogSource},
+};
+
+const RPC_PATH: &str = "/rpc";
+
+/// Code used by SurrealDB for errors that are not part of the JSON-RPC specification.
+const QUERY_ERROR_CODE: i64 = -32000;
+
+const AUTHENTICATION_ERROR: &str = "There was a problem with authentication";
+
+/// Duration of a scope session when its `DEFINE SCOPE` statement has no `SESSION` clause, like the SurrealDB server.
+const DEFAULT_SCOPE_SESSION_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// File of the storage folder where the datastore is saved.
+const SNAPSHOT_FILE_NAME: &str = "embedded.surql";
+const SNAPSHOT_DEBOUNCE_DURATION: Duration = Duration::from_millis(200);
+
+/// Credentials of the root user, the only system user that can sign in on the embedded server.
+#[derive(Debug, Clone)]
+struct RootCredentials {
+    username: String,
+    password: String,
+}
+
+/// State shared by every connection of the embedded server.
+struct Server {
+    datastore: Datastore,
+    credentials: RootCredentials,
+    snapshot: Option<Snapshot>,
+}
+
+/// Export of the datastore saved in the storage folder after each change, for the `file:` and `rocksdb:` storages.
+/// The `surrealdb` crate only persists data with RocksDB, which needs clang to be built:
+/// exporting the memory datastore keeps `cargo install ultime` enough to get a persistent database.
+struct Snapshot {
+    path: PathBuf,
+    changed: Notify,
+}
+
+/// Claims of the token of a scope user, signed with the secret of the scope like the SurrealDB server does.
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    iat: i64,
+    nbf: i64,
+    exp: i64,
+    iss: String,
+    #[serde(rename = "NS")]
+    ns: String,
+    #[serde(rename = "DB")]
+    db: String,
+    #[serde(rename = "SC")]
+    sc: String,
+    #[serde(rename = "ID")]
+    id: String,
+}
+
+/// Start an in-process SurrealDB server that exposes the same WebSocket endpoint as `surreal start`.
+/// Only the root user, the scope users and the methods used by the rust client are supported.
+pub async fn start_server(port: u16, surrealdb_config: &SurrealdbConfig) -> Result<SocketAddr> {
+    let datastore = Datastore::new("memory")
+        .await
+        .context("Cannot create the embedded SurrealDB datastore")?;
+
+    let snapshot = surrealdb_config.storage.path().map(|path| Snapshot {
+        path: path.join(SNAPSHOT_FILE_NAME),
+        changed: Notify::new(),
+    });
+    if let Some(snapshot) = &snapshot {
+        load_snapshot(&datastore, &snapshot.path)
+            .await
+            .with_context(|| format!("Cannot load {}", snapshot.path.display()))?;
+    }
+
+    let server = Arc::new(Server {
+        datastore,
+        credentials: RootCredentials {
+            username: surrealdb_config.username.to_string(),
+            password: surrealdb_config.password.to_string(),
+        },
+        snapshot,
+    });
+
+    if server.snapshot.is_some() {
+        tokio::spawn(save_snapshot_on_changes(server.clone()));
+    }
+
+    let listener = TcpListener::bind((surrealdb_config.bind_host(), port)).await?;
+    let address = listener.local_addr()?;
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let server = server.clone();
+
+            tokio::spawn(async move {
+                if let Err(error) = handle_connection(stream, &server).await {
+                    logger::log_error(
+                        LogSource::Db,
+                        format_args!("Connection closed with error: {:#}", error),
+                    );
+                }
+            });
+        }
+    });
+
+    Ok(address)
+}
+
+impl Server {
+    fn notify_change(&self) {
+        if let Some(snapshot) = &self.snapshot {
+            snapshot.changed.notify_one();
+        }
+    }
+
+    async fn get_scope(&self, ns: &str, db: &str, sc: &str) -> Result<DefineScopeStatement> {
+        let mut transaction = self.datastore.transaction(false, false).await?;
+        let scope = transaction.get_sc(ns, db, sc).await;
+        transaction.cancel().await?;
+
+        Ok(scope?)
+    }
+}
+
+/// Execute the export of a previous run, if any.
+async fn load_snapshot(datastore: &Datastore, path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let responses = datastore
+        .execute(&content, &Session::for_kv(), None, false)
+        .await?;
+
+    for response in responses {
+        response.result?;
+    }
+
+    Ok(())
+}
+
+/// Save the datastore once for each burst of changes, until the server stops.
+async fn save_snapshot_on_changes(server: Arc<Server>) {
+    let Some(snapshot) = &server.snapshot else {
+        return;
+    };
+
+    loop {
+        snapshot.changed.notified().await;
+        tokio::time::sleep(SNAPSHOT_DEBOUNCE_DURATION).await;
+
+        if let Err(error) = save_snapshot(&server.datastore, &snapshot.path).await {
+            logger::log_error(
+                LogSource::Db,
+                format_args!("Cannot save the embedded SurrealDB datastore: {:#}", error),
+            );
+        }
+    }
+}
+
+/// Export every database of the datastore, each one preceded by its `USE` statement.
+async fn save_snapshot(datastore: &Datastore, path: &Path) -> Result<()> {
+    let mut transaction = datastore.transaction(false, false).await?;
+    let mut databases = vec![];
+    for ns in transaction.all_ns().await?.iter() {
+        for db in transaction.all_db(&ns.name).await?.iter() {
+            databases.push((ns.name.clone(), db.name.clone()));
+        }
+    }
+    transaction.cancel().await?;
+
+    let mut content = vec![];
+    for (ns, db) in databases {
+        content.extend(format!("USE NS {} DB {};\n", ns, db).into_bytes());
+        content.extend(export_database(datastore, ns, db).await?);
+    }
+
+    if let Some(parent_dir) = path.parent() {
+        std::fs::create_dir_all(parent_dir)?;
+    }
+
+    // 💡 replace the previous export at once, so that it is never read half written
+    let temporary_path = path.with_extension("surql.tmp");
+    std::fs::write(&temporary_path, content)?;
+    std::fs::rename(&temporary_path, path)?;
+
+    Ok(())
+}
+
+async fn export_database(datastore: &Datastore, ns: Ident, db: Ident) -> Result<Vec<u8>> {
+    let (sender, receiver) = surrealdb::channel::new(1);
+
+    let read_export = async {
+        let mut content = vec![];
+        while let Ok(bytes) = receiver.recv().await {
+            content.extend(bytes);
+        }
+        content
+    };
+
+    let (result, content) = tokio::join!(
+        datastore.export(ns.to_raw(), db.to_raw(), sender),
+        read_export
+    );
+    result?;
+
+    Ok(content)
+}
+
+/// State of a WebSocket connection, like a session of the SurrealDB server.
+struct Connection<'a> {
+    server: &'a Server,
+    session: Session,
+    vars: BTreeMap<String, Value>,
+}
+
+async fn handle_connection(stream: TcpStream, server: &Server) -> Result<()> {
+    let mut socket = tokio_tungstenite::accept_hdr_async(stream, check_rpc_path).await?;
+
+    let mut connection = Connection {
+        server,
+        session: Session::default(),
+        vars: BTreeMap::new(),
+    };
+
+    while let Some(message) = socket.next().await {
+        let request = match message? {
+            Message::Binary(bytes) => bung::from_slice::<Value>(&bytes)
+                .map_err(|error| anyhow!("Invalid request: {}", error))?,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let response = connection.handle_request(request).await;
+        socket.send(Message::Binary(response)).await?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::result_large_err)] // 💡 signature expected by tungstenite
+fn check_rpc_path(
+    request: &Request,
+    response: HandshakeResponse,
+) -> Result<HandshakeResponse, ErrorResponse> {
+    if request.uri().path() == RPC_PATH {
+        return Ok(response);
+    }
+
+    let mut error = ErrorResponse::new(None);
+    *error.status_mut() = StatusCode::NOT_FOUND;
+    Err(error)
+}
+
+/// Response of the RPC endpoint, serialized like the SurrealDB server so that the rust client can read it.
+#[derive(Serialize)]
+struct RpcResponse {
+    id: Value,
+    #[serde(flatten)]
+    content: RpcContent,
+}
+
+#[derive(Serialize)]
+enum RpcContent {
+    #[serde(rename = "result")]
+    Success(RpcResult),
+    #[serde(rename = "error")]
+    Failure { code: i64, message: String },
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RpcResult {
+    Query(Vec<(String, &'static str, QueryResult)>),
+    Other(Value),
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum QueryResult {
+    Value(Value),
+    Error(String),
+}
+
+impl Connection<'_> {
+    async fn handle_request(&mut self, request: Value) -> Vec<u8> {
+        let (id, content) = match request {
+            Value::Object(Object(mut request)) => {
+                let id = request.remove("id").unwrap_or_default();
+                let method = request.remove("method").unwrap_or_default().as_string();
+                let params = match request.remove("params") {
+                    Some(Value::Array(Array(params))) => params,
+                    _ => vec![],
+                };
+
+                let content = match self.execute(&method, params).await {
+                    Ok(result) => RpcContent::Success(result),
+                    Err(error) => RpcContent::Failure {
+                        code: QUERY_ERROR_CODE,
+                        message: error.to_string(),
+                    },
+                };
+
+                (id, content)
+            }
+            _ => (
+                Value::None,
+                RpcContent::Failure {
+                    code: -32600,
+                    message: "Invalid request".to_string(),
+                },
+            ),
+        };
+
+        let response = RpcResponse { id, content };
+
+        // 💡 the binary protocol uses the typed representation of values
+        surrealdb::sql::serde::serialize_internal(|| {
+            bung::to_vec_named(&response).unwrap_or_default()
+        })
+    }
+
+    async fn execute(&mut self, method: &str, mut params: Vec<Value>) -> Result<RpcResult> {
+        match method {
+            "ping" | "health" => Ok(RpcResult::Other(Value::None)),
+            "version" => Ok(RpcResult::Other(
+                format!("surrealdb-{} (embedded)", surrealdb::env::VERSION).into(),
+            )),
+            "use" => {
+                if let [Value::Strand(Strand(ns)), Value::Strand(Strand(db))] = &mut params[..] {
+                    self.session.ns = Some(mem::take(ns));
+                    self.session.db = Some(mem::take(db));
+                }
+                Ok(RpcResult::Other(Value::None))
+            }
+            "signin" => self.signin(params).await,
+            "signup" => self.signup(params).await,
+            "authenticate" => self.authenticate(params).await,
+            "invalidate" => {
+                self.session = Session {
+                    ns: self.session.ns.take(),
+                    db: self.session.db.take(),
+                    ..Default::default()
+                };
+                Ok(RpcResult::Other(Value::None))
+            }
+            "set" | "let" => {
+                if let [Value::Strand(Strand(key)), value, ..] = &mut params[..] {
+                    self.vars.insert(mem::take(key), mem::take(value));
+                }
+                Ok(RpcResult::Other(Value::None))
+            }
+            "unset" => {
+                if let [Value::Strand(Strand(key)), ..] = &params[..] {
+                    self.vars.remove(key);
+                }
+                Ok(RpcResult::Other(Value::None))
+            }
+            "query" => {
+                self.server.notify_change();
+                self.query(params).await
+            }
+            "select" => self.crud(method, params).await,
+            "create" | "update" | "merge" | "patch" | "delete" => {
+                self.server.notify_change();
+                self.crud(method, params).await
+            }
+            _ => Err(anyhow!(
+                "Method '{}' is not supported by the embedded SurrealDB engine",
+                method
+            )),
+        }
+    }
+
+    async fn signin(&mut self, params: Vec<Value>) -> Result<RpcResult> {
+        let credentials = match params.into_iter().next() {
+            Some(Value::Object(Object(credentials))) => credentials,
+            _ => return Err(anyhow!("Invalid credentials")),
+        };
+
+        if credentials.contains_key("sc") {
+            return self.scope_signin("signin", credentials).await;
+        }
+
+        let username = credentials
+            .get("user")
+            .map(|value| value.clone().as_string());
+        let password = credentials
+            .get("pass")
+            .map(|value| value.clone().as_string());
+
+        let root = &self.server.credentials;
+        if username.as_deref() != Some(&root.username)
+            || password.as_deref() != Some(&root.password)
+        {
+            return Err(anyhow!(AUTHENTICATION_ERROR));
+        }
+
+        self.session.au = Arc::new(Auth::Kv);
+
+        Ok(RpcResult::Other(Value::None))
+    }
+
+    async fn signup(&mut self, params: Vec<Value>) -> Result<RpcResult> {
+        let credentials = match params.into_iter().next() {
+            Some(Value::Object(Object(credentials))) if credentials.contains_key("sc") => {
+                credentials
+            }
+            _ => return Err(anyhow!("Signup is only available for scope users")),
+        };
+
+        // 💡 the `SIGNUP` clause usually creates the user
+        self.server.notify_change();
+
+        self.scope_signin("signup", credentials).await
+    }
+
+    /// Run the `SIGNIN`/`SIGNUP` clause of a `DEFINE SCOPE` statement, the remaining credentials being its variables.
+    /// The record returned by the clause becomes `$auth` of the session, and the `ID` of the returned token.
+    async fn scope_signin(
+        &mut self,
+        method: &str,
+        mut credentials: BTreeMap<String, Value>,
+    ) -> Result<RpcResult> {
+        let mut take = |key: &str| credentials.remove(key).map(Value::as_string);
+        let (Some(ns), Some(db), Some(sc)) = (take("ns"), take("db"), take("sc")) else {
+            return Err(anyhow!("Invalid credentials"));
+        };
+
+        let scope = self.server.get_scope(&ns, &db, &sc).await?;
+        let clause = match method {
+            "signup" => scope.signup.clone(),
+            _ => scope.signin.clone(),
+        };
+        let Some(clause) = clause else {
+            return Err(anyhow!("The scope '{}' does not allow to {}", sc, method));
+        };
+
+        // 💡 like the SurrealDB server, the clause is not restricted by the permissions of the tables
+        let system_session = Session::for_kv().with_ns(&ns).with_db(&db);
+        let record = self
+            .server
+            .datastore
+            .compute(clause, &system_session, Some(credentials), false)
+            .await
+            .ok()
+            .and_then(|value| value.record())
+            .ok_or_else(|| anyhow!(AUTHENTICATION_ERROR))?;
+
+        let now = Utc::now().timestamp();
+        let session_duration = scope
+            .session
+            .map(|duration| duration.0)
+            .unwrap_or(DEFAULT_SCOPE_SESSION_DURATION);
+        let claims = Claims {
+            iat: now,
+            nbf: now,
+            exp: now + session_duration.as_secs() as i64,
+            iss: "SurrealDB".to_string(),
+            ns,
+            db,
+            sc,
+            id: record.to_string(),
+        };
+
+        let token = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS512),
+            &claims,
+            &EncodingKey::from_secret(scope.code.as_bytes()),
+        )?;
+
+        self.session = to_scope_session(claims, record);
+
+        Ok(RpcResult::Other(token.into()))
+    }
+
+    /// Restore the session of a scope user from the token returned by `signin`/`signup`.
+    async fn authenticate(&mut self, params: Vec<Value>) -> Result<RpcResult> {
+        let token = match params.into_iter().next() {
+            Some(Value::Strand(Strand(token))) => token,
+            _ => return Err(anyhow!("Invalid token")),
+        };
+
+        // 💡 the scope is read from the token before its signature can be verified with the secret of the scope
+        let mut insecure_validation = Validation::new(Algorithm::HS512);
+        insecure_validation.insecure_disable_signature_validation();
+        let claims = jsonwebtoken::decode::<Claims>(
+            &token,
+            &DecodingKey::from_secret(&[]),
+            &insecure_validation,
+        )
+        .map_err(|_| anyhow!(AUTHENTICATION_ERROR))?
+        .claims;
+
+        let scope = self
+            .server
+            .get_scope(&claims.ns, &claims.db, &claims.sc)
+            .await
+            .map_err(|_| anyhow!(AUTHENTICATION_ERROR))?;
+
+        let claims = jsonwebtoken::decode::<Claims>(
+            &token,
+            &DecodingKey::from_secret(scope.code.as_bytes()),
+            &Validation::new(Algorithm::HS512),
+        )
+        .map_err(|_| anyhow!(AUTHENTICATION_ERROR))?
+        .claims;
+
+        let record =
+            surrealdb::sql::thing(&claims.id).map_err(|_| anyhow!(AUTHENTICATION_ERROR))?;
+
+        self.session = to_scope_session(claims, record);
+
+        Ok(RpcResult::Other(Value::None))
+    }
+
+    async fn query(&mut self, params: Vec<Value>) -> Result<RpcResult> {
+        let mut params = params.into_iter();
+
+        let query = match params.next() {
+            Some(Value::Strand(Strand(query))) => query,
+            _ => return Err(anyhow!("Invalid query")),
+        };
+
+        let mut vars = self.vars.clone();
+        if let Some(Value::Object(Object(mut bindings))) = params.next() {
+            vars.append(&mut bindings);
+        }
+
+        let responses = self
+            .server
+            .datastore
+            .execute(&query, &self.session, Some(vars), false)
+            .await?;
+
+        let results = responses
+            .into_iter()
+            .map(|response| {
+                let time = format!("{:?}", response.time);
+                match response.result {
+                    Ok(value) => (time, "OK", QueryResult::Value(value)),
+                    Err(error) => (time, "ERR", QueryResult::Error(error.to_string())),
+                }
+            })
+            .collect();
+
+        Ok(RpcResult::Query(results))
+    }
+
+    async fn crud(&mut self, method: &str, mut params: Vec<Value>) -> Result<RpcResult> {
+        let (what, data) = match &mut params[..] {
+            [what] => (mem::take(what), Value::None),
+            [what, data, ..] => (mem::take(what), mem::take(data)),
+            _ => return Err(anyhow!("Invalid params")),
+        };
+
+        let one = what.is_thing() || method == "create";
+        let what = match what.could_be_table() {
+            Value::Array(Array(values)) => Values(values),
+            value => Values(vec![value]),
+        };
+        let data = match data {
+            Value::None | Value::Null => None,
+            value => Some(value),
+        };
+
+        let statement = match method {
+            "select" => Statement::Select(SelectStatement {
+                what,
+                expr: Fields(vec![Field::All], false),
+                ..Default::default()
+            }),
+            "create" => Statement::Create(CreateStatement {
+                what,
+                data: data.map(Data::ContentExpression),
+                output: Some(Output::After),
+                ..Default::default()
+            }),
+            "update" | "merge" | "patch" => {
+                let (data, output) = match method {
+                    "merge" => (data.map(Data::MergeExpression), Output::After),
+                    "patch" => (data.map(Data::PatchExpression), Output::Diff),
+                    _ => (data.map(Data::ContentExpression), Output::After),
+                };
+                Statement::Update(UpdateStatement {
+                    what,
+                    data,
+                    output: Some(output),
+                    ..Default::default()
+                })
+            }
+            _ => Statement::Delete(DeleteStatement {
+                what,
+                output: Some(Output::Before),
+                ..Default::default()
+            }),
+        };
+
+        let query = Query(Statements(vec![statement]));
+        let responses = self
+            .server
+            .datastore
+            .process(query, &self.session, Some(self.vars.clone()), false)
+            .await?;
+
+        Ok(RpcResult::Other(take_result(one, responses)?))
+    }
+}
+
+/// Session of a scope user, with `$auth` and `$token` like on the SurrealDB server.
+fn to_scope_session(claims: Claims, record: Thing) -> Session {
+    let mut session = Session::for_sc(claims.ns.as_str(), claims.db.as_str(), claims.sc.as_str());
+
+    let token = BTreeMap::from([
+        ("iat".to_string(), Value::from(claims.iat)),
+        ("nbf".to_string(), Value::from(claims.nbf)),
+        ("exp".to_string(), Value::from(claims.exp)),
+        ("iss".to_string(), Value::from(claims.iss)),
+        ("NS".to_string(), Value::from(claims.ns)),
+        ("DB".to_string(), Value::from(claims.db)),
+        ("SC".to_string(), Value::from(claims.sc)),
+        ("ID".to_string(), Value::from(claims.id)),
+    ]);
+    session.tk = Some(Value::Object(Object(token)));
+    session.sd = Some(Value::from(record));
+
+    session
+}
+
+/// Result of the single statement executed, unwrapped when a single record is expected.
+fn take_result(one: bool, responses: Vec<Response>) -> Result<Value> {
+    let value = match responses.into_iter().next() {
+        Some(response) => response.result?,
+        None => Value::None,
+    };
+
+    match (one, value) {
+        (true, Value::Array(Array(mut values))) if values.len() <= 1 => {
+            Ok(values.pop().unwrap_or_default())
+        }
+        (_, value) => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SurrealdbStorage;
+    use pretty_assertions::assert_eq;
+    use surrealdb::{
+        engine::remote::ws::Ws,
+        opt::auth::{Root, Scope},
+        Surreal,
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Post {
+        title: String,
+    }
+
+    async fn connect() -> Surreal<surrealdb::engine::remote::ws::Client> {
+        let surrealdb_config = SurrealdbConfig::default();
+        let address = start_server(0, &surrealdb_config).await.unwrap();
+
+        let db = Surreal::new::<Ws>(address.to_string()).await.unwrap();
+
+        db.signin(Root {
+            username: "root",
+            password: "root",
+        })
+        .await
+        .unwrap();
+
+        db.use_ns("test").use_db("test").await.unwrap();
+
+        db
+    }
+
+    #[tokio::test]
+    async fn execute_query_on_embedded_server() {
+        let db = connect().await;
+
+        let mut response = db
+            .query("CREATE post:1 SET title = $title; SELECT title FROM post;")
+            .bind(("title", "Hello world"))
+            .await
+            .unwrap();
+
+        let posts: Vec<Post> = response.take(1).unwrap();
+
+        assert_eq!(
+            posts,
+            vec![Post {
+                title: "Hello world".to_string()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn execute_crud_methods_on_embedded_server() {
+        let db = connect().await;
+
+        let created: Post = db
+            .create(("post", "1"))
+            .content(Post {
+                title: "Hello".to_string(),
+            })
+            .await
+            .unwrap();
+        let found: Option<Post> = db.select(("post", "1")).await.unwrap();
+        let all: Vec<Post> = db.select("post").await.unwrap();
+        let deleted: Option<Post> = db.delete(("post", "1")).await.unwrap();
+        let remaining: Vec<Post> = db.select("post").await.unwrap();
+
+        assert_eq!(created.title, "Hello");
+        assert_eq!(found, Some(created));
+        assert_eq!(all.len(), 1);
+        assert!(deleted.is_some());
+        assert!(remaining.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fails_to_signin_with_invalid_credentials() {
+        let surrealdb_config = SurrealdbConfig::default();
+        let address = start_server(0, &surrealdb_config).await.unwrap();
+
+        let db = Surreal::new::<Ws>(address.to_string()).await.unwrap();
+
+        let result = db
+            .signin(Root {
+                username: "root",
+                password: "wrong",
+            })
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn signup_and_authenticate_with_scope() {
+        let db = connect().await;
+
+        db.query(
+            "DEFINE TABLE user SCHEMALESS PERMISSIONS FOR select WHERE id = $auth.id;
+            DEFINE SCOPE user_scope
+                SIGNUP (CREATE user SET username = $username, password = crypto::argon2::generate($password))
+                SIGNIN (SELECT * FROM user WHERE username = $username AND crypto::argon2::compare(password, $password));",
+        )
+        .await
+        .unwrap();
+
+        #[derive(Serialize)]
+        struct Credentials<'a> {
+            username: &'a str,
+            password: &'a str,
+        }
+
+        let credentials = Credentials {
+            username: "john",
+            password: "secret",
+        };
+        let scope = |params| Scope {
+            namespace: "test",
+            database: "test",
+            scope: "user_scope",
+            params,
+        };
+
+        db.signup(scope(&credentials)).await.unwrap();
+        let token = db.signin(scope(&credentials)).await.unwrap();
+        db.invalidate().await.unwrap();
+        db.authenticate(token).await.unwrap();
+
+        let mut response = db.query("SELECT username FROM $auth").await.unwrap();
+        let usernames: Vec<String> = response.take("username").unwrap();
+
+        assert_eq!(usernames, vec!["john".to_string()]);
+
+        let wrong_credentials = Credentials {
+            username: "john",
+            password: "wrong",
+        };
+        assert!(db.signin(scope(&wrong_credentials)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn fails_to_authenticate_with_forged_token() {
+        let db = connect().await;
+
+        db.query("DEFINE SCOPE user_scope SIGNIN (SELECT * FROM user)")
+            .await
+            .unwrap();
+
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            iat: now,
+            nbf: now,
+            exp: now + 60,
+            iss: "SurrealDB".to_string(),
+            ns: "test".to_string(),
+            db: "test".to_string(),
+            sc: "user_scope".to_string(),
+            id: "user:john".to_string(),
+        };
+        let token = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS512),
+            &claims,
+            &EncodingKey::from_secret(b"not the secret of the scope"),
+        )
+        .unwrap();
+
+        let result = db.authenticate(token).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn keep_data_of_file_storage_between_servers() {
+        let storage_dir = assert_fs::TempDir::new().unwrap();
+        let surrealdb_config = SurrealdbConfig {
+            storage: SurrealdbStorage::File(storage_dir.to_path_buf()),
+            ..Default::default()
+        };
+
+        let address = start_server(0, &surrealdb_config).await.unwrap();
+        let db = Surreal::new::<Ws>(address.to_string()).await.unwrap();
+        db.signin(Root {
+            username: "root",
+            password: "root",
+        })
+        .await
+        .unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+
+        let _: Post = db
+            .create(("post", "1"))
+            .content(Post {
+                title: "Hello".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let snapshot_path = storage_dir.join(SNAPSHOT_FILE_NAME);
+        for _ in 0..50 {
+            if snapshot_path.exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let address = start_server(0, &surrealdb_config).await.unwrap();
+        let db = Surreal::new::<Ws>(address.to_string()).await.unwrap();
+        db.signin(Root {
+            username: "root",
+            password: "root",
+        })
+        .await
+        .unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+
+        let post: Option<Post> = db.select(("post", "1")).await.unwrap();
+
+        assert_eq!(
+            post,
+            Some(Post {
+                title: "Hello".to_string()
+            })
+        );
+    }
+}
@@ -25,8 +25,9 @@ impl IntoAttribute for CommentTarget {
 
 #[server(CommentPostOrComment, "/api")]
 pub async fn comment(cx: Scope, target: String, content: String) -> Result<(), ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, opt::auth::Root, sql::Thing, Surreal};
+    use surrealdb::sql::Thing;
 
+    use crate::db::connection::get_db_with_token;
     use crate::db::mutations::comment::mutate_comment;
 
     fn parse_target(target: String) -> CommentTarget {
@@ -54,19 +55,10 @@ pub async fn comment(cx: Scope, target: String, content: String) -> Result<(), S
         })
         .ok_or(ServerFnError::ServerError("Cannot get token".to_string()))?;
 
-    let db = Surreal::new::<Ws>("localhost:8000").await.map_err(|_| {
-        ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string())
+    let db = get_db_with_token(token).await.map_err(|_| {
+        ServerFnError::ServerError("Cannot authenticate to SurrealDB".to_string())
     })?;
 
-    db.use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    db.authenticate(token)
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot authenticate".to_string()))?;
-
     let post_id = match &target {
         CommentTarget::BlogPost(id) => Some(id),
         CommentTarget::Comment(_) => None,
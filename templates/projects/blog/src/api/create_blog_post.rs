@@ -9,7 +9,9 @@ pub async fn create_blog_post(
     content: String,
 ) -> Result<Post, ServerFnError> {
     use serde::{Deserialize, Serialize};
-    use surrealdb::{engine::remote::ws::Ws, opt::auth::Root, sql::Thing, Surreal};
+    use surrealdb::sql::Thing;
+
+    use crate::db::connection::get_db_with_token;
 
     #[derive(Serialize, Deserialize)]
     struct CreateBlogPostContent {
@@ -29,19 +31,10 @@ pub async fn create_blog_post(
         })
         .ok_or(ServerFnError::ServerError("Cannot get token".to_string()))?;
 
-    let db = Surreal::new::<Ws>("localhost:8000").await.map_err(|_| {
-        ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string())
+    let db = get_db_with_token(token).await.map_err(|_| {
+        ServerFnError::ServerError("Cannot authenticate to SurrealDB".to_string())
     })?;
 
-    db.use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    db.authenticate(token)
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot authenticate".to_string()))?;
-
     // TODO : use crate::db::crud::post::create_post function
     let post: Post = db
         .create("post")
@@ -7,20 +7,22 @@ pub async fn sign_in(cx: Scope, username: String, password: String) -> Result<()
         engine::remote::ws::Ws, opt::auth::Jwt, opt::auth::Scope, sql::Value, Surreal,
     };
 
+    use crate::db::connection::{get_address, DATABASE, NAMESPACE};
+
     #[derive(Debug, Serialize)]
     struct SignInAuthParams {
         username: String,
         password: String,
     }
 
-    let db = Surreal::new::<Ws>("localhost:8000").await.map_err(|_| {
+    let db = Surreal::new::<Ws>(get_address()).await.map_err(|_| {
         ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string())
     })?;
 
     let token: Jwt = db
         .signin(Scope {
-            namespace: "test",
-            database: "test",
+            namespace: NAMESPACE,
+            database: DATABASE,
             scope: "user_scope",
             params: SignInAuthParams { username, password },
         })
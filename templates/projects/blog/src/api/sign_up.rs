@@ -15,6 +15,8 @@ pub async fn sign_up(
         engine::remote::ws::Ws, opt::auth::Jwt, opt::auth::Scope, sql::Value, Surreal,
     };
 
+    use crate::db::connection::{get_address, DATABASE, NAMESPACE};
+
     #[derive(Debug, Serialize)]
     struct SignUpAuthParams {
         username: String,
@@ -22,14 +24,14 @@ pub async fn sign_up(
         password: String,
     }
 
-    let db = Surreal::new::<Ws>("localhost:8000").await.map_err(|_| {
+    let db = Surreal::new::<Ws>(get_address()).await.map_err(|_| {
         ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string())
     })?;
 
     let token: Jwt = db
         .signup(Scope {
-            namespace: "test",
-            database: "test",
+            namespace: NAMESPACE,
+            database: DATABASE,
             scope: "user_scope",
             params: SignUpAuthParams {
                 username,
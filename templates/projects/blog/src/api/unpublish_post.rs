@@ -3,8 +3,9 @@ use leptos::*;
 #[server(UnpublishPost, "/api")]
 pub async fn unpublish_post(cx: Scope, post_id: String) -> Result<(), ServerFnError> {
     use serde::{Deserialize, Serialize};
-    use surrealdb::{engine::remote::ws::Ws, opt::auth::Root, sql::Thing, Surreal};
+    use surrealdb::sql::Thing;
 
+    use crate::db::connection::get_db_with_token;
     use crate::db::events::unpublish_post::UnpublishPostData;
 
     let request = expect_context::<actix_web::HttpRequest>(cx);
@@ -18,19 +19,10 @@ pub async fn unpublish_post(cx: Scope, post_id: String) -> Result<(), ServerFnEr
         })
         .ok_or(ServerFnError::ServerError("Cannot get token".to_string()))?;
 
-    let db = Surreal::new::<Ws>("localhost:8000").await.map_err(|_| {
-        ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string())
+    let db = get_db_with_token(token).await.map_err(|_| {
+        ServerFnError::ServerError("Cannot authenticate to SurrealDB".to_string())
     })?;
 
-    db.use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    db.authenticate(token)
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot authenticate".to_string()))?;
-
     let data = UnpublishPostData {
         post_id: ("post".to_string(), post_id.to_string()).into(),
     };
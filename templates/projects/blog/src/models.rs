@@ -1,2 +1 @@
-pub mod mutations;
 pub mod queries;
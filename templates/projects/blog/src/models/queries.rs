@@ -1,16 +1,5 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct PostsQueryItem {
-    pub id: String,
-    pub title: String,
-    pub content: String,
-    pub status: String,
-    pub number_of_comments: u16,
-}
-
-pub type PostsQuery = Vec<PostsQueryItem>;
-
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PostByIdQueryComments {
     pub id: String,
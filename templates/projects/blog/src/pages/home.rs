@@ -4,7 +4,7 @@ use leptos_router::*;
 use crate::{
     api::{fetch_blog_posts, CreateBlogPost},
     components::navbar::Navbar,
-    models::queries::PostsQueryItem,
+    db::queries::posts::PostsQueryItem,
 };
 
 #[component]
@@ -100,7 +100,7 @@ pub fn BlogPost(cx: Scope, post: PostsQueryItem) -> impl IntoView {
         <li>
             <A href={href}>
                 <h2>{post.title}</h2>
-                <p inner_html={post.content} />
+                <p inner_html={post.content.unwrap_or_default()} />
 
                 <div class="post-list-footer">
                     <span>{post.status}</span>
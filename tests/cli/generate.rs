@@ -1,4 +1,6 @@
 mod component;
 mod db;
 mod endpoint;
+mod migration;
 mod page;
+mod resource;
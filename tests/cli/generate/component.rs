@@ -54,3 +54,40 @@ pub fn MyComponent(cx: Scope) -> impl IntoView {
 
     Ok(())
 }
+
+#[test]
+fn register_new_leptos_component_in_components_module() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir).arg("new").arg("ultime-project");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("ultime-project");
+
+    for _ in 0..2 {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir)
+            .arg("generate")
+            .arg("component")
+            .arg("my-component");
+
+        cmd.assert().success();
+    }
+
+    let src_dir = project_dir.child("src");
+
+    src_dir
+        .child("components.rs")
+        .assert("pub mod my_component;\n");
+
+    let lib_content = std::fs::read_to_string(src_dir.child("lib.rs"))?;
+    assert!(lib_content.starts_with("pub mod app;\nmod components;\nmod pages;\n"));
+
+    temp_dir.close()?;
+
+    Ok(())
+}
@@ -1,5 +1,10 @@
 use anyhow::{Context, Result};
-use assert_fs::{fixture::PathChild, prelude::PathAssert};
+use assert_fs::{
+    fixture::{FileWriteStr, PathChild},
+    prelude::PathAssert,
+};
+use itertools::Itertools;
+use std::{process::Stdio, time::Duration};
 
 use crate::helpers::*;
 
@@ -20,13 +25,19 @@ fn generate_db_module_from_empty_template() -> Result<()> {
         let mut cmd = create_cmd()?;
         cmd.current_dir(&project_dir).arg("generate").arg("db");
 
-        cmd.assert().success().stdout("db folder generated...\n");
+        cmd.assert()
+            .success()
+            .stdout("db folder generated...\n  + db::crud::script_migration\n");
 
         let src_dir = project_dir.child("src");
         let db_file = src_dir.child("db.rs");
 
         assert!(db_file.exists());
-        db_file.assert("pub mod crud;");
+        db_file.assert(
+            r#"#[cfg(feature = "ssr")]
+pub mod connection;
+pub mod crud;"#,
+        );
 
         let db_dir = src_dir.child("db");
         assert!(db_dir.exists());
@@ -68,7 +79,21 @@ fn generate_db_module_from_blog_template() -> Result<()> {
         let mut cmd = create_cmd()?;
         cmd.current_dir(&project_dir).arg("generate").arg("db");
 
-        cmd.assert().success().stdout("db folder generated...\n");
+        cmd.assert().success().stdout(
+            "db folder generated...
+  + db::crud::comment
+  + db::crud::permission
+  + db::crud::post
+  + db::crud::script_migration
+  + db::crud::user
+  + db::events::publish_post
+  + db::events::unpublish_post
+  + db::mutations::comment
+  + db::queries::navbar
+  + db::queries::post_by_id
+  + db::queries::posts
+",
+        );
 
         // TODO : Assert on project_dir
         let src_dir = project_dir.child("src");
@@ -76,10 +101,12 @@ fn generate_db_module_from_blog_template() -> Result<()> {
 
         assert!(db_file.exists());
         db_file.assert(
-            "pub mod crud;
+            r#"#[cfg(feature = "ssr")]
+pub mod connection;
+pub mod crud;
 pub mod events;
 pub mod mutations;
-pub mod queries;",
+pub mod queries;"#,
         );
 
         let db_dir = src_dir.child("db");
@@ -93,15 +120,19 @@ pub mod queries;",
             let crud_files = crud_files.collect::<Result<Vec<_>, _>>()?;
             assert_eq!(crud_files.len(), 5);
 
-            let crud_file_names = crud_files.iter().map(|f| f.file_name()).collect::<Vec<_>>();
+            let crud_file_names = crud_files
+                .iter()
+                .map(|f| f.file_name())
+                .sorted()
+                .collect::<Vec<_>>();
             assert_eq!(
                 crud_file_names,
                 vec![
-                    "permission.rs",
-                    "user.rs",
                     "comment.rs",
-                    "script_migration.rs",
+                    "permission.rs",
                     "post.rs",
+                    "script_migration.rs",
+                    "user.rs",
                 ]
             );
         }
@@ -117,10 +148,11 @@ pub mod queries;",
             let events_file_names = events_files
                 .iter()
                 .map(|f| f.file_name())
+                .sorted()
                 .collect::<Vec<_>>();
             assert_eq!(
                 events_file_names,
-                vec!["unpublish_post.rs", "publish_post.rs"]
+                vec!["publish_post.rs", "unpublish_post.rs"]
             );
         }
 
@@ -135,6 +167,7 @@ pub mod queries;",
             let mutations_file_names = mutations_files
                 .iter()
                 .map(|f| f.file_name())
+                .sorted()
                 .collect::<Vec<_>>();
             assert_eq!(mutations_file_names, vec!["comment.rs"]);
         }
@@ -150,10 +183,11 @@ pub mod queries;",
             let queries_file_names = queries_files
                 .iter()
                 .map(|f| f.file_name())
+                .sorted()
                 .collect::<Vec<_>>();
             assert_eq!(
                 queries_file_names,
-                vec!["posts.rs", "post_by_id.rs", "navbar.rs"]
+                vec!["navbar.rs", "post_by_id.rs", "posts.rs"]
             );
         }
     }
@@ -162,3 +196,803 @@ pub mod queries;",
 
     Ok(())
 }
+
+#[test]
+fn generate_db_connection_using_project_config() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir).arg("new").arg("ultime-project");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("ultime-project");
+
+    project_dir.child("ultime.toml").write_str(
+        r#"[surrealdb]
+address = "localhost:8001"
+username = "admin"
+password = "secret"
+ns = "blog"
+db = "main"
+"#,
+    )?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    let connection_file = project_dir.child("src").child("db").child("connection.rs");
+
+    assert!(connection_file.is_file());
+    connection_file.assert(
+        r#"use surrealdb::{
+    engine::remote::ws::{Client, Ws},
+    opt::auth::Root,
+    Surreal,
+};
+use tokio::sync::OnceCell;
+
+/// Namespace and database of `ultime.toml`.
+pub const NAMESPACE: &str = "blog";
+pub const DATABASE: &str = "main";
+
+static DB: OnceCell<Surreal<Client>> = OnceCell::const_new();
+
+/// Client shared by every endpoint, connected to SurrealDB on first use.
+/// The client is never replaced: when the SurrealDB instance restarts, it reconnects by itself and signs in again,
+/// the requests sent while the instance is down fail.
+pub async fn get_db() -> surrealdb::Result<&'static Surreal<Client>> {
+    DB.get_or_try_init(connect).await
+}
+
+/// Client authenticated with the token of the signed in user, so that the `PERMISSIONS` of the tables apply.
+/// The authentication is bound to the connection, which cannot be shared between users.
+pub async fn get_db_with_token(token: String) -> surrealdb::Result<Surreal<Client>> {
+    let db = Surreal::new::<Ws>(get_address()).await?;
+
+    db.use_ns(NAMESPACE).use_db(DATABASE).await?;
+
+    db.authenticate(token).await?;
+
+    Ok(db)
+}
+
+async fn connect() -> surrealdb::Result<Surreal<Client>> {
+    let db = Surreal::new::<Ws>(get_address()).await?;
+
+    db.signin(Root {
+        username: "admin",
+        password: "secret",
+    })
+    .await?;
+
+    db.use_ns(NAMESPACE).use_db(DATABASE).await?;
+
+    Ok(db)
+}
+
+/// Address of the SurrealDB instance started by `ultime`, for the endpoints that need their own client (e.g. to sign in a user).
+pub fn get_address() -> String {
+    std::env::var("ULTIME_SURREALDB_ADDRESS").unwrap_or_else(|_| "localhost:8001".to_string())
+}"#,
+    );
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn generate_model_stub_when_query_type_cannot_be_inferred() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir).arg("new").arg("ultime-project");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("ultime-project");
+
+    project_dir
+        .child("queries")
+        .child("migrations.surql")
+        .write_str(
+            "SELECT script_name, string::uppercase(script_name) AS upper FROM script_migration;",
+        )?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success().stdout(
+            "db folder generated...\n  + db::crud::script_migration\n  + db::queries::migrations\n",
+        );
+
+        let src_dir = project_dir.child("src");
+
+        src_dir.child("models.rs").assert("pub mod queries;\n");
+        src_dir.child("models").child("queries.rs").assert(
+            "use serde::{Deserialize, Serialize};
+
+// TODO : Define the properties returned by `queries/migrations.surql`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationsQueryItem {}
+
+pub type MigrationsQuery = Vec<MigrationsQueryItem>;
+",
+        );
+    }
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn generate_inferred_query_model() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir).arg("new").arg("ultime-project");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("ultime-project");
+
+    project_dir
+        .child("queries")
+        .child("migrations.surql")
+        .write_str("SELECT meta::id(id) AS id, script_name FROM script_migration;")?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success().stdout(
+            "db folder generated...\n  + db::crud::script_migration\n  + db::queries::migrations\n",
+        );
+
+        let src_dir = project_dir.child("src");
+
+        assert!(!src_dir.child("models.rs").exists());
+        let query_file_content =
+            std::fs::read_to_string(src_dir.child("db").child("queries").child("migrations.rs"))?;
+        assert!(query_file_content.starts_with(
+            "use serde::{Deserialize, Serialize};
+use surrealdb::{Surreal, Connection, Result};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationsQueryItem {
+    pub id: String,
+    #[serde(skip_serializing_if = \"Option::is_none\")]
+    pub script_name: Option<String>,
+}
+
+pub type MigrationsQuery = Vec<MigrationsQueryItem>;
+"
+        ));
+    }
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn skip_generation_when_db_module_is_up_to_date() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success().stdout("db folder up to date\n");
+    }
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn report_changed_and_removed_modules() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    std::fs::remove_file(project_dir.child("queries").child("navbar.surql"))?;
+    project_dir
+        .child("queries")
+        .child("posts.surql")
+        .write_str("SELECT meta::id(id) AS id, title FROM post;")?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success().stdout(
+            "db folder generated...
+  ~ db::queries::posts
+  - db::queries::navbar
+",
+        );
+    }
+
+    let queries_dir = project_dir.child("src").child("db").child("queries");
+
+    assert!(!queries_dir.child("navbar.rs").exists());
+    assert!(queries_dir.child("posts.rs").exists());
+    project_dir
+        .child("src")
+        .child("db")
+        .child("queries.rs")
+        .assert("pub mod post_by_id;\npub mod posts;");
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn remove_db_module_when_nothing_is_generated() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir).arg("new").arg("ultime-project");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("ultime-project");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    std::fs::remove_dir_all(project_dir.child("schemas"))?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert()
+            .success()
+            .stdout("db folder generated...\n  - db::crud::script_migration\n");
+    }
+
+    let src_dir = project_dir.child("src");
+
+    assert!(!src_dir.child("db.rs").exists());
+    assert!(!src_dir.child("db").exists());
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn check_db_module_is_up_to_date() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    // 💡 the manifest is not committed, so it should not be needed to check the db module
+    std::fs::remove_dir_all(project_dir.child("target"))?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir)
+            .arg("generate")
+            .arg("db")
+            .arg("--check");
+
+        cmd.assert().success().stdout("db folder is up to date\n");
+    }
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn fails_to_check_db_module_when_not_generated() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir).arg("new").arg("ultime-project");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("ultime-project");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir)
+            .arg("generate")
+            .arg("db")
+            .arg("--check");
+
+        let assert = cmd.assert().failure().stderr(
+            "Error: db folder is not up to date, run `ultime generate db` to regenerate it\n",
+        );
+
+        let stdout = String::from_utf8(assert.get_output().stdout.clone())?;
+        assert!(stdout.contains("--- a/src/db.rs\n+++ b/src/db.rs\n@@ -0,0 +1,3 @@\n"));
+        assert!(stdout.contains("+++ b/src/db/connection.rs\n"));
+        assert!(stdout.contains("+++ b/src/db/crud/script_migration.rs\n"));
+    }
+
+    let src_dir = project_dir.child("src");
+
+    assert!(!src_dir.child("db.rs").exists());
+    assert!(!src_dir.child("db").exists());
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn remove_untracked_db_files_reported_by_check() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    // 💡 a fresh clone without manifest, with a file of a query removed in the meantime
+    std::fs::remove_dir_all(project_dir.child("target"))?;
+    let stale_file = project_dir
+        .child("src")
+        .child("db")
+        .child("queries")
+        .child("old_posts.rs");
+    stale_file.write_str("pub fn old_posts() {}")?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir)
+            .arg("generate")
+            .arg("db")
+            .arg("--check");
+
+        let assert = cmd.assert().failure();
+
+        let stdout = String::from_utf8(assert.get_output().stdout.clone())?;
+        assert!(stdout
+            .contains("--- a/src/db/queries/old_posts.rs\n+++ b/src/db/queries/old_posts.rs\n"));
+    }
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert()
+            .success()
+            .stdout("db folder generated...\n  - db::queries::old_posts\n");
+    }
+
+    assert!(!stale_file.exists());
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir)
+            .arg("generate")
+            .arg("db")
+            .arg("--check");
+
+        cmd.assert().success().stdout("db folder is up to date\n");
+    }
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn fails_to_check_db_module_after_query_change() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    std::fs::remove_file(project_dir.child("queries").child("navbar.surql"))?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir)
+            .arg("generate")
+            .arg("db")
+            .arg("--check");
+
+        let assert = cmd.assert().failure();
+
+        let stdout = String::from_utf8(assert.get_output().stdout.clone())?;
+        assert!(stdout.contains(
+            "--- a/src/db/queries.rs
++++ b/src/db/queries.rs
+@@ -1,3 +1,2 @@
+-pub mod navbar;
+ pub mod post_by_id;
+ pub mod posts;"
+        ));
+        assert!(stdout.contains("--- a/src/db/queries/navbar.rs\n+++ b/src/db/queries/navbar.rs\n"));
+    }
+
+    assert!(project_dir
+        .child("src")
+        .child("db")
+        .child("queries")
+        .child("navbar.rs")
+        .exists());
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn regenerate_db_module_on_query_changes_in_watch_mode() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    let mut child = create_std_cmd()?
+        .current_dir(&project_dir)
+        .arg("generate")
+        .arg("db")
+        .arg("--watch")
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let queries_dir = project_dir.child("src").child("db").child("queries");
+
+    let is_generated = wait_until(|| queries_dir.child("navbar.rs").exists());
+    if is_generated {
+        std::thread::sleep(Duration::from_millis(500));
+
+        std::fs::rename(
+            project_dir.child("queries").child("navbar.surql"),
+            project_dir.child("queries").child("header.surql"),
+        )?;
+    }
+
+    let is_regenerated = is_generated
+        && wait_until(|| {
+            queries_dir.child("header.rs").exists() && !queries_dir.child("navbar.rs").exists()
+        });
+
+    child.kill()?;
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(is_regenerated, "db module not regenerated:\n{}", stdout);
+    assert!(stdout.contains("Watching queries folder...\n"));
+    assert!(stdout.contains("  + db::queries::header\n"));
+    assert!(stdout.contains("  - db::queries::navbar\n"));
+    assert!(!stdout.contains("  ~ db::crud::"));
+
+    project_dir
+        .child("src")
+        .child("db")
+        .child("queries.rs")
+        .assert("pub mod header;\npub mod post_by_id;\npub mod posts;");
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+fn wait_until(predicate: impl Fn() -> bool) -> bool {
+    for _ in 0..100 {
+        if predicate() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    false
+}
+
+#[test]
+fn report_parse_errors_and_generate_other_files() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    project_dir
+        .child("queries")
+        .child("posts.surql")
+        .write_str("SELECT * FROM post;\nSELEC * FRM comment;")?;
+    project_dir
+        .child("mutations")
+        .child("empty.surql")
+        .write_str("")?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        let assert = cmd.assert().failure();
+        let stderr = String::from_utf8(assert.get_output().stderr.clone())?;
+
+        assert_eq!(
+            stderr,
+            "error: cannot parse mutations/empty.surql
+  = the file does not contain any statement
+error: cannot parse queries/posts.surql
+ --> queries/posts.surql:2:1
+  |
+2 | SELEC * FRM comment;
+  | ^
+  = near 'SELEC * FRM comment;'
+Error: 2 files could not be parsed
+"
+        );
+    }
+
+    let queries_dir = project_dir.child("src").child("db").child("queries");
+
+    assert!(queries_dir.child("navbar.rs").exists());
+    assert!(!queries_dir.child("posts.rs").exists());
+    project_dir
+        .child("src")
+        .child("db")
+        .child("queries.rs")
+        .assert("pub mod navbar;\npub mod post_by_id;");
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn keep_generated_module_of_broken_query() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    let posts_file = project_dir
+        .child("src")
+        .child("db")
+        .child("queries")
+        .child("posts.rs");
+    let posts_content = std::fs::read_to_string(&posts_file)?;
+
+    project_dir
+        .child("queries")
+        .child("posts.surql")
+        .write_str("SELEC * FRM post;")?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert()
+            .failure()
+            .stdout("db folder up to date\n")
+            .stderr(
+                "error: cannot parse queries/posts.surql
+ --> queries/posts.surql:1:1
+  |
+1 | SELEC * FRM post;
+  | ^
+  = near 'SELEC * FRM post;'
+Error: 1 file could not be parsed
+",
+            );
+    }
+
+    posts_file.assert(posts_content.as_str());
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn keep_generated_queries_and_models_while_schema_is_broken() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    let src_dir = project_dir.child("src");
+    let posts_file = src_dir.child("db").child("queries").child("posts.rs");
+    let posts_content = std::fs::read_to_string(&posts_file)?;
+    let models_file = src_dir.child("models").child("queries.rs");
+    let models_content = std::fs::read_to_string(&models_file)?;
+
+    project_dir
+        .child("schemas")
+        .child("post.surql")
+        .write_str("DEFINE TABLE post SCHEMAFULL;\nDEFINE FIELD title ON post TYPO string;")?;
+    project_dir
+        .child("queries")
+        .child("drafts.surql")
+        .write_str("SELECT * FROM post WHERE status = 'draft';")?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        let assert = cmd.assert().failure();
+
+        let stderr = String::from_utf8(assert.get_output().stderr.clone())?;
+        assert!(stderr.contains("error: cannot parse schemas/post.surql\n"));
+    }
+
+    posts_file.assert(posts_content.as_str());
+    models_file.assert(models_content.as_str());
+    assert!(!src_dir
+        .child("db")
+        .child("queries")
+        .child("drafts.rs")
+        .exists());
+
+    temp_dir.close()?;
+
+    Ok(())
+}
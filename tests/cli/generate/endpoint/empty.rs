@@ -47,3 +47,45 @@ pub async fn fetch_blog_posts() -> Result<(), ServerFnError> {
 
     Ok(())
 }
+
+#[test]
+fn register_new_leptos_endpoint_in_api_module() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir).arg("new").arg("ultime-project");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("ultime-project");
+
+    for name in ["fetchBlogPosts", "createBlogPost"] {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir)
+            .arg("generate")
+            .arg("endpoint")
+            .arg(name);
+
+        cmd.assert().success();
+    }
+
+    let src_dir = project_dir.child("src");
+
+    src_dir.child("api.rs").assert(
+        "mod create_blog_post;
+mod fetch_blog_posts;
+
+pub use self::create_blog_post::*;
+pub use self::fetch_blog_posts::*;
+",
+    );
+
+    let lib_content = std::fs::read_to_string(src_dir.child("lib.rs"))?;
+    assert!(lib_content.starts_with("pub mod api;\npub mod app;\nmod pages;\n"));
+
+    temp_dir.close()?;
+
+    Ok(())
+}
@@ -79,27 +79,13 @@ use crate::db::events::publish_post::PublishPostData;
 pub async fn publish_post(
     data: PublishPostData,
 ) -> Result<(), ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    crate::db::events::publish_post::publish_post(&db, data)
+    crate::db::events::publish_post::publish_post(db, data)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply event publish_post".to_string()))?;
 
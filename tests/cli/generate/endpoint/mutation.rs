@@ -73,7 +73,7 @@ fn generate_new_leptos_endpoint_from_mutation() -> Result<()> {
     endpoint_file.assert(
         r#"use leptos::*;
 
-use crate::models::mutations::CommentMutation;
+use crate::db::mutations::comment::CommentMutation;
 
 #[server(Comment, "/api")]
 pub async fn comment(
@@ -81,29 +81,15 @@ pub async fn comment(
     comment_id: Option<String>,
     content: String,
 ) -> Result<CommentMutation, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::mutations::comment::mutate_comment;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    let result = mutate_comment(&db, post_id, comment_id, content)
+    let result = mutate_comment(db, post_id, comment_id, content)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply mutation comment".to_string()))?;
 
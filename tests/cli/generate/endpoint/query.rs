@@ -73,33 +73,19 @@ fn generate_new_leptos_endpoint_from_query() -> Result<()> {
     endpoint_file.assert(
         r#"use leptos::*;
 
-use crate::models::queries::PostsQuery;
+use crate::db::queries::posts::PostsQuery;
 
 #[server(FetchBlogPosts, "/api")]
 pub async fn fetch_blog_posts() -> Result<PostsQuery, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::queries::posts::query_posts;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    let result = query_posts(&db)
+    let result = query_posts(db)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply query posts".to_string()))?;
 
@@ -152,35 +138,21 @@ fn generate_new_leptos_endpoint_from_query_with_params() -> Result<()> {
     endpoint_file.assert(
         r#"use leptos::*;
 
-use crate::models::queries::PostByIdQuery;
+use crate::db::queries::post_by_id::PostByIdQuery;
 
 #[server(FetchPostDetails, "/api")]
 pub async fn fetch_post_details(
     post_id: String,
 ) -> Result<PostByIdQuery, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::queries::post_by_id::query_post_by_id;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    let result = query_post_by_id(&db, post_id)
+    let result = query_post_by_id(db, post_id)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply query post_by_id".to_string()))?;
 
@@ -233,33 +205,19 @@ fn generate_new_leptos_endpoint_from_query_using_rs_file_extension() -> Result<(
     endpoint_file.assert(
         r#"use leptos::*;
 
-use crate::models::queries::PostsQuery;
+use crate::db::queries::posts::PostsQuery;
 
 #[server(FetchBlogPosts, "/api")]
 pub async fn fetch_blog_posts() -> Result<PostsQuery, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::queries::posts::query_posts;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    let result = query_posts(&db)
+    let result = query_posts(db)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply query posts".to_string()))?;
 
@@ -312,35 +270,95 @@ fn generate_new_leptos_endpoint_from_query_using_surql_file_extension() -> Resul
     endpoint_file.assert(
         r#"use leptos::*;
 
-use crate::models::queries::PostsQuery;
+use crate::db::queries::posts::PostsQuery;
 
 #[server(FetchBlogPosts, "/api")]
 pub async fn fetch_blog_posts() -> Result<PostsQuery, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::queries::posts::query_posts;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
+    let result = query_posts(db)
         .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
+        .map_err(|_| ServerFnError::ServerError("Cannot apply query posts".to_string()))?;
+
+    Ok(result)
+}"#,
+    );
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn generate_new_leptos_endpoint_from_query_with_scope_auth() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
 
-    db
-        .use_ns("test")
-        .use_db("test")
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&project_dir)
+        .arg("generate")
+        .arg("endpoint")
+        .arg("fetchPostById")
+        .arg("--from-query")
+        .arg("post_by_id")
+        .arg("--auth")
+        .arg("scope");
+
+    cmd.assert()
+        .success()
+        .stdout("Endpoint fetchPostById successfully created\n");
+
+    let endpoint_file = project_dir
+        .child("src")
+        .child("api")
+        .child("fetch_post_by_id.rs");
+
+    assert!(endpoint_file.is_file());
+    endpoint_file.assert(
+        r#"use leptos::*;
+
+use crate::db::queries::post_by_id::PostByIdQuery;
+
+#[server(FetchPostById, "/api")]
+pub async fn fetch_post_by_id(
+    cx: Scope,
+    post_id: String,
+) -> Result<PostByIdQuery, ServerFnError> {
+    use crate::db::connection::get_db_with_token;
+
+    use crate::db::queries::post_by_id::query_post_by_id;
+
+    let request = expect_context::<actix_web::HttpRequest>(cx);
+    let token = request
+        .cookie("access_token")
+        .map(|cookie| cookie.value().to_string())
+        .ok_or_else(|| ServerFnError::ServerError("Cannot get token".to_string()))?;
+
+    let db = get_db_with_token(token)
         .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
+        .map_err(|_| ServerFnError::ServerError("Cannot authenticate to SurrealDB".to_string()))?;
 
-    let result = query_posts(&db)
+    let result = query_post_by_id(&db, post_id)
         .await
-        .map_err(|_| ServerFnError::ServerError("Cannot apply query posts".to_string()))?;
+        .map_err(|_| ServerFnError::ServerError("Cannot apply query post_by_id".to_string()))?;
 
     Ok(result)
 }"#,
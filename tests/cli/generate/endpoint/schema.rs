@@ -1,5 +1,9 @@
 use anyhow::Result;
-use assert_fs::{fixture::PathChild, prelude::PathAssert};
+use assert_fs::{
+    fixture::{FileWriteStr, PathChild, PathCopy},
+    prelude::PathAssert,
+};
+use std::path::{Path, PathBuf};
 
 use crate::helpers::*;
 
@@ -79,33 +83,19 @@ use crate::db::crud::post::Post;
 
 #[server(ListPosts, "/api")]
 pub async fn list_posts() -> Result<Vec<Post>, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::crud::post::get_all_post;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    get_all_post(&db)
+    let result = get_all_post(db)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply function get_all_post".to_string()))?;
 
-    Ok(())
+    Ok(result)
 }"#,
     );
 
@@ -160,35 +150,21 @@ use crate::db::crud::post::Post;
 
 #[server(GetPost, "/api")]
 pub async fn get_post(
-    id: &'static str,
+    id: String,
 ) -> Result<Post, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::crud::post::get_post;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    get_post(&db, id)
+    let result = get_post(db, &id)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply function get_post".to_string()))?;
 
-    Ok(())
+    Ok(result)
 }"#,
     );
 
@@ -243,35 +219,21 @@ use crate::db::crud::post::Post;
 
 #[server(FindPost, "/api")]
 pub async fn find_post(
-    id: &'static str,
+    id: String,
 ) -> Result<Option<Post>, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::crud::post::find_post;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    find_post(&db, id)
+    let result = find_post(db, &id)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply function find_post".to_string()))?;
 
-    Ok(())
+    Ok(result)
 }"#,
     );
 
@@ -328,33 +290,19 @@ use crate::db::crud::post::Post;
 pub async fn create_post(
     data: Post,
 ) -> Result<Post, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::crud::post::create_post;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    create_post(&db, data)
+    let result = create_post(db, data)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply function create_post".to_string()))?;
 
-    Ok(())
+    Ok(result)
 }"#,
     );
 
@@ -409,35 +357,22 @@ use crate::db::crud::post::Post;
 
 #[server(UpdatePost, "/api")]
 pub async fn update_post(
+    id: String,
     data: Post,
-) -> Result<Post, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+) -> Result<Option<Post>, ServerFnError> {
+    use crate::db::connection::get_db;
 
     use crate::db::crud::post::update_post;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    update_post(&db, data)
+    let result = update_post(db, &id, data)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply function update_post".to_string()))?;
 
-    Ok(())
+    Ok(result)
 }"#,
     );
 
@@ -492,35 +427,21 @@ use crate::db::crud::post::Post;
 
 #[server(DeletePost, "/api")]
 pub async fn delete_post(
-    data: Post,
-) -> Result<Post, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    id: String,
+) -> Result<Option<Post>, ServerFnError> {
+    use crate::db::connection::get_db;
 
     use crate::db::crud::post::delete_post;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
-
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
-
-    delete_post(&db, data)
+    let result = delete_post(db, &id)
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot apply function delete_post".to_string()))?;
 
-    Ok(())
+    Ok(result)
 }"#,
     );
 
@@ -575,34 +496,167 @@ use crate::db::crud::post::Post;
 
 #[server(DeleteAllPosts, "/api")]
 pub async fn delete_all_posts() -> Result<Vec<Post>, ServerFnError> {
-    use surrealdb::{engine::remote::ws::Ws, Surreal, opt::auth::Root};
+    use crate::db::connection::get_db;
 
     use crate::db::crud::post::delete_all_post;
 
-    let db = Surreal::new::<Ws>("localhost:8000")
+    let db = get_db()
         .await
         .map_err(|_| ServerFnError::ServerError("Cannot open connection to SurrealDB".to_string()))?;
 
-    db
-        .signin(Root {
-            username: "root",
-            password: "root",
-        })
+    let result = delete_all_post(db)
         .await
-        .map_err(|_| ServerFnError::ServerError("Cannot signin to SurrealDB".to_string()))?;
+        .map_err(|_| ServerFnError::ServerError("Cannot apply function delete_all_post".to_string()))?;
 
-    db
-        .use_ns("test")
-        .use_db("test")
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot use namespace and database".to_string()))?;
+    Ok(result)
+}"#,
+    );
 
-    delete_all_post(&db)
-        .await
-        .map_err(|_| ServerFnError::ServerError("Cannot apply function delete_all_post".to_string()))?;
+    temp_dir.close()?;
 
     Ok(())
-}"#,
+}
+
+/// Endpoints generated for each method, with the name of their file in `/api`.
+const SCHEMA_ENDPOINTS: [(&str, &str); 7] = [
+    ("list", "list_posts"),
+    ("get", "get_post"),
+    ("find", "find_post"),
+    ("create", "create_post"),
+    ("update", "update_post"),
+    ("delete", "delete_post"),
+    ("delete-all", "delete_all_posts"),
+];
+
+/// Authentications of the generated endpoints, used as suffix of their names.
+const ENDPOINT_AUTHS: [&str; 2] = ["root", "scope"];
+
+#[test]
+fn compile_endpoints_from_schema_against_generated_crud_module() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&project_dir).arg("generate").arg("db");
+
+        cmd.assert().success();
+    }
+
+    let mut endpoint_names = vec![];
+
+    for (method, file_name) in SCHEMA_ENDPOINTS {
+        for auth in ENDPOINT_AUTHS {
+            let endpoint_name = format!("{}_{}", file_name, auth);
+
+            let mut cmd = create_cmd()?;
+            cmd.current_dir(&project_dir)
+                .arg("generate")
+                .arg("endpoint")
+                .arg(&endpoint_name)
+                .arg("--from-schema")
+                .arg("post")
+                .arg("--method")
+                .arg(method)
+                .arg("--auth")
+                .arg(auth);
+
+            cmd.assert().success();
+
+            endpoint_names.push(endpoint_name);
+        }
+    }
+
+    // 💡 the other modules of the blog template are not needed, only the generated endpoints and the crud module
+    // are checked with the server dependencies of the template (`leptos` being built on the stable toolchain)
+    let check_dir = temp_dir.child("check");
+    let src_dir = project_dir.child("src");
+
+    check_dir.child("Cargo.toml").write_str(
+        r#"[package]
+name = "endpoint-check"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+actix-web = { version = "4", features = ["macros"] }
+chrono = { version = "0.4", features = ["serde"] }
+leptos = { version = "0.3", default-features = false, features = ["serde", "ssr", "stable"] }
+serde = { version = "1", features = ["derive"] }
+surrealdb = { version = "1.0.0-beta.9" }
+tokio = { version = "1", features = ["parking_lot", "sync"] }
+# older versions do not compile with recent toolchains
+wasm-bindgen = "0.2.88"
+
+[features]
+default = ["ssr"]
+ssr = []
+"#,
+    )?;
+    // 💡 same versions of the dependencies shared with ultime, surrealdb in particular
+    check_dir.copy_from(env!("CARGO_MANIFEST_DIR"), &["Cargo.lock"])?;
+
+    let api_modules = endpoint_names
+        .iter()
+        .map(|endpoint_name| format!("    pub mod {};", endpoint_name))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    check_dir.child("src").child("lib.rs").write_str(&format!(
+        "#![allow(dead_code)]
+
+mod api {{
+{}
+}}
+mod db;
+",
+        api_modules
+    ))?;
+    check_dir
+        .child("src")
+        .child("db.rs")
+        .write_str("pub mod connection;\npub mod crud;")?;
+    check_dir.child("src").copy_from(
+        &src_dir,
+        &["db/connection.rs", "db/crud.rs", "db/crud/*.rs"],
+    )?;
+    check_dir.child("src").copy_from(
+        &src_dir,
+        &endpoint_names
+            .iter()
+            .map(|endpoint_name| format!("api/{}.rs", endpoint_name))
+            .collect::<Vec<_>>(),
+    )?;
+
+    // 💡 shared between runs, so that the dependencies are only built once
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("target"))
+        .join("endpoint-check");
+
+    let output = std::process::Command::new(env!("CARGO"))
+        .current_dir(&check_dir)
+        .env("CARGO_TARGET_DIR", target_dir)
+        .arg("check")
+        .arg("--quiet")
+        .output()?;
+
+    assert!(
+        output.status.success(),
+        "generated endpoints do not compile:\n{}",
+        String::from_utf8_lossy(&output.stderr)
     );
 
     temp_dir.close()?;
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use assert_fs::{
+    fixture::{FileWriteStr, PathChild},
+    prelude::PathAssert,
+};
+use regex::Regex;
+
+use crate::helpers::*;
+
+fn get_created_file_name(stdout: &str) -> Result<String> {
+    let regex = Regex::new(r"^Migration (\d{8}_\d{6}_AddTagToPost\.surql) successfully created\n")?;
+
+    let file_name = regex
+        .captures(stdout)
+        .and_then(|captures| captures.get(1))
+        .map(|file_name| file_name.as_str().to_string())
+        .unwrap_or_default();
+
+    Ok(file_name)
+}
+
+#[test]
+fn generate_migration_with_down_file() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&temp_dir)
+        .arg("generate")
+        .arg("migration")
+        .arg("add-tag-to-post")
+        .arg("--down");
+
+    let assert = cmd.assert().success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone())?;
+
+    let file_name = get_created_file_name(&stdout)?;
+    assert!(!file_name.is_empty(), "unexpected output: {}", stdout);
+    assert!(stdout.ends_with(&format!(
+        "Down migration {} successfully created\n",
+        file_name
+    )));
+
+    let migrations_dir = temp_dir.child("migrations");
+    migrations_dir.child(&file_name).assert("");
+    migrations_dir.child("down").child(&file_name).assert("");
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn generate_migration_with_schemas_diff() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    temp_dir
+        .child("schemas/post.surql")
+        .write_str("DEFINE TABLE post SCHEMAFULL;\nDEFINE FIELD tags ON post TYPE array;\n")?;
+    temp_dir
+        .child("migrations/definitions/_initial.json")
+        .write_str(r#"{"schemas":"DEFINE TABLE post SCHEMAFULL;\n","events":""}"#)?;
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&temp_dir)
+        .arg("generate")
+        .arg("migration")
+        .arg("AddTagToPost")
+        .arg("--down")
+        .arg("--diff-from-last-migration");
+
+    let assert = cmd.assert().success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone())?;
+
+    let file_name = get_created_file_name(&stdout)?;
+    assert!(!file_name.is_empty(), "unexpected output: {}", stdout);
+
+    let migrations_dir = temp_dir.child("migrations");
+    migrations_dir
+        .child(&file_name)
+        .assert("DEFINE FIELD tags ON post TYPE array;\n");
+    migrations_dir
+        .child("down")
+        .child(&file_name)
+        .assert("REMOVE FIELD tags ON post;\n");
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn fails_to_generate_migration_diff_without_applied_migrations() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&temp_dir)
+        .arg("generate")
+        .arg("migration")
+        .arg("AddTagToPost")
+        .arg("--diff-from-last-migration");
+
+    cmd.assert().failure().stderr(
+        "Error: Cannot find migrations/definitions/_initial.json, apply the migrations first with `ultime migrate up`\n",
+    );
+
+    assert!(!temp_dir.child("migrations").exists());
+
+    temp_dir.close()?;
+
+    Ok(())
+}
@@ -54,3 +54,46 @@ pub fn HomePage(cx: Scope) -> impl IntoView {
 
     Ok(())
 }
+
+#[test]
+fn register_new_leptos_page_in_pages_module() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&project_dir)
+        .arg("generate")
+        .arg("page")
+        .arg("about");
+
+    cmd.assert()
+        .success()
+        .stdout("Page about successfully created\n");
+
+    let pages_file = project_dir.child("src").child("pages.rs");
+    pages_file.assert(
+        "pub mod about;
+pub mod home;
+pub mod post;
+pub mod sign_in;
+pub mod sign_out;
+pub mod sign_up;
+",
+    );
+
+    temp_dir.close()?;
+
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use anyhow::Result;
+use assert_fs::{
+    fixture::{FileWriteStr, PathChild},
+    prelude::PathAssert,
+};
+
+use crate::helpers::*;
+
+#[test]
+fn generate_resource_from_schema() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    project_dir.child("schemas").child("tag.surql").write_str(
+        "DEFINE TABLE tag SCHEMAFULL;
+
+DEFINE FIELD name ON tag TYPE string ASSERT $value != NONE;
+DEFINE FIELD color ON tag TYPE option<string>;
+DEFINE FIELD aliases ON tag TYPE array ASSERT $value != NONE;
+",
+    )?;
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&project_dir)
+        .arg("generate")
+        .arg("resource")
+        .arg("tag");
+
+    cmd.assert().success().stdout(
+        r#"Resource tag successfully created
+
+Add the routes of its pages to src/app.rs:
+
+use crate::pages::tag::{TagCreatePage, TagDetailsPage, TagEditPage, TagListPage};
+
+<Route path="/tag" view=|cx| view! { cx, <TagListPage/> }/>
+<Route path="/tag/new" view=|cx| view! { cx, <TagCreatePage/> }/>
+<Route path="/tag/:id" view=|cx| view! { cx, <TagDetailsPage/> }/>
+<Route path="/tag/:id/edit" view=|cx| view! { cx, <TagEditPage/> }/>
+
+Register its server functions in src/main.rs:
+
+use my_blog::api::tag::{CreateTag, DeleteAllTag, DeleteTag, FindTag, GetTag, ListTag, UpdateTag};
+
+let _ = CreateTag::register();
+let _ = DeleteAllTag::register();
+let _ = DeleteTag::register();
+let _ = FindTag::register();
+let _ = GetTag::register();
+let _ = ListTag::register();
+let _ = UpdateTag::register();
+
+Add the inputs of the following fields to the create/edit pages, they cannot be read from a text input: aliases
+"#,
+    );
+
+    let src_dir = project_dir.child("src");
+
+    src_dir.child("api").child("tag.rs").assert(
+        "mod create_tag;
+mod delete_all_tag;
+mod delete_tag;
+mod find_tag;
+mod get_tag;
+mod list_tag;
+mod update_tag;
+
+pub use self::create_tag::*;
+pub use self::delete_all_tag::*;
+pub use self::delete_tag::*;
+pub use self::find_tag::*;
+pub use self::get_tag::*;
+pub use self::list_tag::*;
+pub use self::update_tag::*;
+",
+    );
+
+    for endpoint in [
+        "create_tag",
+        "delete_all_tag",
+        "delete_tag",
+        "find_tag",
+        "get_tag",
+        "list_tag",
+        "update_tag",
+    ] {
+        assert!(src_dir
+            .child("api")
+            .child("tag")
+            .child(format!("{}.rs", endpoint))
+            .is_file());
+    }
+
+    src_dir.child("pages").child("tag.rs").assert(
+        "mod create;
+mod details;
+mod edit;
+mod list;
+
+pub use self::create::*;
+pub use self::details::*;
+pub use self::edit::*;
+pub use self::list::*;
+",
+    );
+
+    src_dir
+        .child("pages")
+        .child("tag")
+        .child("create.rs")
+        .assert(
+            r#"use leptos::*;
+use leptos_router::*;
+
+use crate::api::tag::CreateTag;
+
+#[component]
+pub fn TagCreatePage(cx: Scope) -> impl IntoView {
+    let create_tag = create_server_action::<CreateTag>(cx);
+
+    view! { cx,
+        <h1>"New tag"</h1>
+
+        <ActionForm action=create_tag>
+            // TODO: add an input for `aliases`, submitted as `data[aliases]`
+            <label>
+                "color"
+                <input type="text" name="data[color]"/>
+            </label>
+            <input type="hidden" name="data[id][tb]" value="tag"/>
+            <label>
+                "id"
+                <input type="text" name="data[id][id][String]"/>
+            </label>
+            <label>
+                "name"
+                <input type="text" name="data[name]"/>
+            </label>
+            <input type="submit" value="Create"/>
+        </ActionForm>
+    }
+}"#,
+        );
+
+    for page in ["details", "edit", "list"] {
+        assert!(src_dir
+            .child("pages")
+            .child("tag")
+            .child(format!("{}.rs", page))
+            .is_file());
+    }
+
+    let api_mod_file_content = std::fs::read_to_string(src_dir.child("api.rs"))?;
+    assert!(api_mod_file_content.contains("pub mod tag;\n"));
+
+    let pages_mod_file_content = std::fs::read_to_string(src_dir.child("pages.rs"))?;
+    assert!(pages_mod_file_content.contains("pub mod tag;\n"));
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn fails_to_generate_resource_when_pages_already_exist() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    {
+        let mut cmd = create_cmd()?;
+        cmd.current_dir(&temp_dir)
+            .arg("new")
+            .arg("my-blog")
+            .arg("--template")
+            .arg("blog");
+
+        cmd.assert().success();
+    }
+
+    let project_dir = temp_dir.child("my-blog");
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&project_dir)
+        .arg("generate")
+        .arg("resource")
+        .arg("post");
+
+    cmd.assert()
+        .failure()
+        .stderr("Error: File src/pages/post.rs already exists\n");
+
+    assert!(!project_dir.child("src").child("api").child("post").exists());
+
+    temp_dir.close()?;
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use assert_fs::fixture::{FileWriteStr, PathChild};
+use regex::Regex;
+
+use crate::helpers::*;
+
+#[test]
+fn create_new_migration_file() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&temp_dir)
+        .arg("migrate")
+        .arg("new")
+        .arg("AddPost");
+
+    let assert = cmd.assert().success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone())?;
+
+    let file_name_regex =
+        Regex::new(r"^Migration (\d{8}_\d{6}_AddPost\.surql) successfully created\n$")?;
+    let file_name = file_name_regex
+        .captures(&stdout)
+        .and_then(|captures| captures.get(1))
+        .map(|file_name| file_name.as_str())
+        .unwrap_or_default();
+
+    assert!(!file_name.is_empty(), "unexpected output: {}", stdout);
+    assert!(temp_dir.child("migrations").child(file_name).is_file());
+
+    temp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn fails_to_apply_migrations_when_database_is_not_running() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    temp_dir
+        .child("ultime.toml")
+        .write_str("[surrealdb]\naddress = \"localhost:1\"\n")?;
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&temp_dir).arg("migrate").arg("up");
+
+    let assert = cmd.assert().failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone())?;
+
+    assert!(stderr.contains("Cannot connect to SurrealDB on localhost:1, is `ultime` running?"));
+
+    temp_dir.close()?;
+
+    Ok(())
+}
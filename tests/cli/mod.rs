@@ -1,2 +1,4 @@
 mod generate;
+mod migrate;
 mod new;
+mod run;
@@ -43,6 +43,7 @@ crate-type = ["cdylib", "rlib"]
 [dependencies]
 actix-files = { version = "0.6", optional = true }
 actix-web = { version = "4", optional = true, features = ["macros"] }
+chrono = { version = "0.4", features = ["serde"] }
 console_error_panic_hook = "0.1"
 cfg-if = "1"
 leptos = { version = "0.3", default-features = false, features = [
@@ -51,8 +52,11 @@ leptos = { version = "0.3", default-features = false, features = [
 leptos_meta = { version = "0.3", default-features = false }
 leptos_actix = { version = "0.3", optional = true }
 leptos_router = { version = "0.3", default-features = false }
+rust_decimal = "1"
 serde = { version = "1.0.163", features = ["derive"] }
+serde_json = "1"
 surrealdb = { version = "1.0.0-beta.9" }
+tokio = { version = "1", features = ["parking_lot", "sync"], optional = true }
 wasm-bindgen = "=0.2.86"
 
 [features]
@@ -65,6 +69,7 @@ ssr = [
   "leptos/ssr",
   "leptos_meta/ssr",
   "leptos_router/ssr",
+  "dep:tokio",
 ]
 
 [package.metadata.leptos]
@@ -162,6 +167,30 @@ playwright/.cache/
         );
     }
 
+    {
+        let ultime_toml_file = my_blog_folder.child("ultime.toml");
+        assert!(ultime_toml_file.is_file(), "ultime.toml file should exists");
+        ultime_toml_file.assert(
+            r#"[surrealdb]
+address = "localhost:8000"
+username = "root"
+password = "root"
+ns = "test"
+db = "test"
+storage = "memory"
+
+[app]
+address = "localhost:3000"
+
+[folders]
+schemas = "schemas"
+events = "events"
+queries = "queries"
+mutations = "mutations"
+"#,
+        );
+    }
+
     {
         let schemas_folder = my_blog_folder.child("schemas");
         assert!(schemas_folder.is_dir(), "schemas dir should exist");
@@ -346,7 +375,7 @@ crate-type = ["cdylib", "rlib"]
 [dependencies]
 actix-files = { version = "0.6", optional = true }
 actix-web = { version = "4", optional = true, features = ["macros"] }
-chrono = "0.4"
+chrono = { version = "0.4", features = ["serde"] }
 console_error_panic_hook = "0.1"
 cfg-if = "1"
 leptos = { version = "0.3", default-features = false, features = [
@@ -355,9 +384,12 @@ leptos = { version = "0.3", default-features = false, features = [
 leptos_meta = { version = "0.3", default-features = false }
 leptos_actix = { version = "0.3", optional = true }
 leptos_router = { version = "0.3", default-features = false }
+rust_decimal = "1"
 serde = { version = "1.0.163", features = ["derive"] }
+serde_json = "1"
 surrealdb = { version = "1.0.0-beta.9" }
 thiserror = "1"
+tokio = { version = "1", features = ["parking_lot", "sync"], optional = true }
 wasm-bindgen = "=0.2.86"
 
 [features]
@@ -370,6 +402,7 @@ ssr = [
   "leptos/ssr",
   "leptos_meta/ssr",
   "leptos_router/ssr",
+  "dep:tokio",
 ]
 
 [package.metadata.leptos]
@@ -467,6 +500,30 @@ playwright/.cache/
         );
     }
 
+    {
+        let ultime_toml_file = my_blog_folder.child("ultime.toml");
+        assert!(ultime_toml_file.is_file(), "ultime.toml file should exists");
+        ultime_toml_file.assert(
+            r#"[surrealdb]
+address = "localhost:8000"
+username = "root"
+password = "root"
+ns = "test"
+db = "test"
+storage = "memory"
+
+[app]
+address = "localhost:3000"
+
+[folders]
+schemas = "schemas"
+events = "events"
+queries = "queries"
+mutations = "mutations"
+"#,
+        );
+    }
+
     {
         let schemas_folder = my_blog_folder.child("schemas");
         assert!(schemas_folder.is_dir(), "schemas dir should exists");
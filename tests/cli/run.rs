@@ -0,0 +1,22 @@
+use anyhow::Result;
+
+use crate::helpers::*;
+
+#[test]
+fn fails_to_run_with_invalid_storage() -> Result<()> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    let mut cmd = create_cmd()?;
+    cmd.current_dir(&temp_dir).arg("--storage").arg("disk");
+
+    let assert = cmd.assert().failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone())?;
+
+    assert!(stderr.contains(
+        "invalid value 'disk' for '--storage <STORAGE>': Invalid storage 'disk', expected 'memory', 'file:<path>' or 'rocksdb:<path>'"
+    ));
+
+    temp_dir.close()?;
+
+    Ok(())
+}
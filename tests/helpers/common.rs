@@ -1,7 +1,13 @@
 use anyhow::Result;
-use assert_cmd::Command;
+use assert_cmd::{cargo::CommandCargoExt, Command};
 
 pub fn create_cmd() -> Result<Command> {
     let cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
     Ok(cmd)
 }
+
+/// Command for long running processes (e.g. watch mode), that must be spawned and killed by the test.
+pub fn create_std_cmd() -> Result<std::process::Command> {
+    let cmd = std::process::Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    Ok(cmd)
+}